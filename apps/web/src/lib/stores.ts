@@ -1,5 +1,5 @@
 import { writable, derived } from 'svelte/store'
-import type { CodeTree, RectNode, LanguageColors } from '@cc/ui'
+import type { CodeTree, CouplingEdge, RectNode, LanguageColors } from '@cc/ui'
 import { normalizeLanguage } from './utils'
 
 export const codeTree = writable<CodeTree | null>(null)
@@ -50,3 +50,15 @@ export const filteredRectNodes = derived(
     return nodes.filter(node => $selectedLanguages.has(normalizeLanguage(node.language, node.path)))
   }
 )
+
+/** Coupling edges by file path, in both directions, strongest first. */
+export const couplings = derived(codeTree, ($codeTree) => {
+  const byPath = new Map<string, { path: string; edge: CouplingEdge }[]>()
+  for (const edge of $codeTree?.edges ?? []) {
+    for (const [a, b] of [[edge.from, edge.to], [edge.to, edge.from]]) {
+      if (!byPath.has(a)) byPath.set(a, [])
+      byPath.get(a)!.push({ path: b, edge })
+    }
+  }
+  return byPath
+})
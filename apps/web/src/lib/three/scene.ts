@@ -4,7 +4,7 @@ import { RenderPass } from 'three/examples/jsm/postprocessing/RenderPass.js'
 import { OutlinePass } from 'three/examples/jsm/postprocessing/OutlinePass.js'
 import { OutputPass } from 'three/examples/jsm/postprocessing/OutputPass.js'
 import { ShaderPass } from 'three/examples/jsm/postprocessing/ShaderPass.js'
-import type { RectNode, BuildingData } from '@cc/ui'
+import type { RectNode, BuildingData, NodeDelta } from '@cc/ui'
 import { animate, stagger } from '../anim'
 import { normalizeLanguage } from '../utils'
 
@@ -63,6 +63,18 @@ const OutlineShader = {
   side: THREE.DoubleSide
 }
 
+// Delta trees: green for growth, red for shrinkage, more saturated the larger
+// the change relative to the old size; grey when unchanged.
+function deltaColor(d: NodeDelta): string {
+  if (d.status === 'added') return '2e9e44'
+  if (d.status === 'removed') return 'b03a2e'
+  const change = d.delta.code || d.delta.loc
+  if (change === 0) return '8a8a8a'
+  const before = Math.max(1, d.before.code ?? d.before.loc)
+  const t = Math.min(1, Math.abs(change) / before)
+  return new THREE.Color().setHSL(change > 0 ? 0.33 : 0, 0.3 + 0.5 * t, 0.45).getHexString()
+}
+
 export class Scene3D {
   private scene: THREE.Scene
   private camera: THREE.PerspectiveCamera
@@ -381,7 +393,9 @@ export class Scene3D {
 
     const filteredRects = rects
 
-    const maxLoc = Math.max(1, ...filteredRects.map(r => (r.metrics?.loc ?? 0)))
+    // Height follows real code lines; trees without the tokei breakdown fall back to loc.
+    const heightOf = (r: RectNode) => r.metrics?.code ?? r.metrics?.loc ?? 0
+    const maxLoc = Math.max(1, ...filteredRects.map(heightOf))
     const targetMaxHeight = 20
     const layoutWidth = Math.max(1, ...filteredRects.map(r => r.x + r.width))
     const layoutHeight = Math.max(1, ...filteredRects.map(r => r.y + r.height))
@@ -393,7 +407,7 @@ export class Scene3D {
     const sz = MAP_TARGET_H / layoutHeight
 
     this.buildingData = filteredRects.map((rect, i) => {
-      const loc = rect.metrics?.loc ?? 0
+      const loc = heightOf(rect)
       const h = ((loc / maxLoc) * targetMaxHeight) * heightScale
       const t = maxLoc > 0 ? loc / maxLoc : 0
 
@@ -401,7 +415,9 @@ export class Scene3D {
       const rawLang = rect.language
       const lang = normalizeLanguage(rawLang, rect.path)
 
-      if (this.languageColors && this.languageColors[lang]) {
+      if (rect.delta) {
+        colorHex = deltaColor(rect.delta)
+      } else if (this.languageColors && this.languageColors[lang]) {
         colorHex = this.languageColors[lang].replace('#', '')
       } else if (this.languageColors && this.languageColors['default']) {
         colorHex = this.languageColors['default'].replace('#', '')
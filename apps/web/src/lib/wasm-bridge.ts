@@ -90,6 +90,12 @@ export async function parseCodeTree(json: string): Promise<CodeTree> {
   return callWorker({ type: 'parse', json })
 }
 
+/** The tree of `after` with a `delta` on every node, removed files included. */
+export async function deltaCodeTree(before: string, after: string): Promise<CodeTree> {
+  if (!initialized) await initializeWasm()
+  return callWorker({ type: 'delta', before, after })
+}
+
 export async function layoutTreemap(tree: CodeTree, width: number, height: number): Promise<RectNode[]> {
   if (!initialized) await initializeWasm()
   return callWorker({ type: 'layout', tree, width, height })
@@ -4,6 +4,8 @@
 declare module '@cc/analyzer' {
   export function init(): Promise<void>
   export function parse_cc_json(json: string): any
+  export function parse_cc_bytes(bytes: Uint8Array): any
+  export function merge_cc_files(files: Array<string | Uint8Array>): any
   export function layout_treemap(tree: any, width: number, height: number): any
   export function get_language_colors(): any
   export default init
@@ -1,4 +1,4 @@
-import init, { parse_cc_json, layout_treemap, get_language_colors } from '@cc/analyzer'
+import init, { parse_cc_json, delta_cc_files, layout_treemap, get_language_colors } from '@cc/analyzer'
 
 let initialized = false
 
@@ -16,12 +16,14 @@ async function ensureInit() {
 type RequestMessage =
   | { id: number; type: 'init' }
   | { id: number; type: 'parse'; json: string }
+  | { id: number; type: 'delta'; before: string; after: string }
   | { id: number; type: 'layout'; tree: any; width: number; height: number }
   | { id: number; type: 'colors' }
 
 type ResponseMessage =
   | { id: number; ok: true; type: 'init' }
   | { id: number; ok: true; type: 'parse'; result: any }
+  | { id: number; ok: true; type: 'delta'; result: any }
   | { id: number; ok: true; type: 'layout'; result: any[] }
   | { id: number; ok: true; type: 'colors'; result: Record<string, string> }
   | { id: number; ok: false; error: string }
@@ -44,6 +46,12 @@ self.onmessage = async (ev: MessageEvent<RequestMessage>) => {
         ;(self as any).postMessage(resp)
         break
       }
+      case 'delta': {
+        const tree = delta_cc_files(msg.before, msg.after)
+        const resp: ResponseMessage = { id: msg.id, ok: true, type: 'delta', result: tree }
+        ;(self as any).postMessage(resp)
+        break
+      }
       case 'layout': {
         const rects = layout_treemap(msg.tree, msg.width, msg.height)
         const resp: ResponseMessage = { id: msg.id, ok: true, type: 'layout', result: rects }
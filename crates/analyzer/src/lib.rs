@@ -1,3 +1,7 @@
+use ryoiki_core::{
+    delta_tree, merge_trees, parse_tree, CouplingEdge, DeltaNode, DeltaStatus, FunctionSpan,
+    GitMetrics, Maintainability, MetricsDelta, Node,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use wasm_bindgen::prelude::*;
@@ -13,16 +17,130 @@ pub struct Metrics {
     pub loc: u32,
     pub complexity: Option<u32>,
     pub functions: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comments: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blanks: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cognitive: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_nesting: Option<u32>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct CodeTree {
     pub name: String,
     pub path: String,
-    pub kind: String, // "file" or "directory"
+    pub kind: String, // "directory", "file" or "function"
     pub metrics: Metrics,
     pub language: Option<String>,
     pub children: Option<Vec<CodeTree>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attributes: Option<HashMap<String, f64>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<GitMetrics>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hotspot_score: Option<f64>,
+    /// Temporal coupling between files; only set on the root.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub edges: Vec<CouplingEdge>,
+    /// Set on every node of a [`delta_cc_files`] tree.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delta: Option<NodeDelta>,
+    /// Set on function nodes, which files of a scan with function depth hold.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function: Option<FunctionSpan>,
+    /// Halstead measures and maintainability index, for coloring by
+    /// maintainability.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub maintainability: Option<Maintainability>,
+}
+
+/// How a node changed between the two projects of [`delta_cc_files`].
+#[derive(Serialize, Deserialize, Clone)]
+pub struct NodeDelta {
+    pub status: DeltaStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub moved_from: Option<String>,
+    pub before: Metrics,
+    pub delta: MetricsDelta,
+}
+
+impl From<&ryoiki_core::Metrics> for Metrics {
+    fn from(m: &ryoiki_core::Metrics) -> Self {
+        Metrics {
+            loc: m.loc as u32,
+            complexity: Some(m.complexity as u32),
+            functions: Some(m.functions as u32),
+            code: Some(m.code as u32),
+            comments: Some(m.comments as u32),
+            blanks: Some(m.blanks as u32),
+            cognitive: Some(m.cognitive as u32),
+            max_nesting: Some(m.max_nesting as u32),
+        }
+    }
+}
+
+impl From<Node> for CodeTree {
+    fn from(node: Node) -> Self {
+        CodeTree {
+            name: node.name,
+            path: node.path,
+            kind: node.kind,
+            metrics: Metrics::from(&node.metrics),
+            language: node.language,
+            children: node
+                .children
+                .map(|c| c.into_iter().map(CodeTree::from).collect()),
+            attributes: if node.attributes.is_empty() {
+                None
+            } else {
+                Some(node.attributes.into_iter().collect())
+            },
+            git: node.git,
+            hotspot_score: node.hotspot_score,
+            edges: node.edges,
+            delta: None,
+            function: node.function,
+            maintainability: node.maintainability,
+        }
+    }
+}
+
+/// Sizes follow the second project; removed nodes keep their old metrics so
+/// they stay visible.
+impl From<DeltaNode> for CodeTree {
+    fn from(node: DeltaNode) -> Self {
+        let current = if node.status == DeltaStatus::Removed {
+            &node.before
+        } else {
+            &node.after
+        };
+        CodeTree {
+            name: node.name,
+            path: node.path,
+            kind: node.kind,
+            metrics: Metrics::from(current),
+            language: node.language,
+            children: node
+                .children
+                .map(|c| c.into_iter().map(CodeTree::from).collect()),
+            attributes: None,
+            git: None,
+            hotspot_score: None,
+            edges: Vec::new(),
+            delta: Some(NodeDelta {
+                status: node.status,
+                moved_from: node.moved_from,
+                before: Metrics::from(&node.before),
+                delta: node.delta,
+            }),
+            function: None,
+            maintainability: None,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -35,19 +153,81 @@ pub struct RectNode {
     pub depth: u32,
     pub metrics: Metrics,
     pub language: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attributes: Option<HashMap<String, f64>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<GitMetrics>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hotspot_score: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delta: Option<NodeDelta>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function: Option<FunctionSpan>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub maintainability: Option<Maintainability>,
+}
+
+/// A JS string or `Uint8Array` as bytes.
+fn js_bytes(value: &JsValue) -> Vec<u8> {
+    match value.as_string() {
+        Some(s) => s.into_bytes(),
+        None => js_sys::Uint8Array::new(value).to_vec(),
+    }
 }
 
+/// Parses a ryoiki tree or an official CodeCharta `cc.json`.
 #[wasm_bindgen]
 pub fn parse_cc_json(json: &str) -> Result<JsValue, JsValue> {
+    parse_cc_bytes(json.as_bytes())
+}
+
+/// Like [`parse_cc_json`], but also accepts gzip-compressed `.cc.json.gz` files.
+#[wasm_bindgen]
+pub fn parse_cc_bytes(bytes: &[u8]) -> Result<JsValue, JsValue> {
     console_error_panic_hook::set_once();
 
-    let tree: CodeTree = serde_json::from_str(json)
-        .map_err(|e| JsValue::from_str(&format!("Parse error: {}", e)))?;
+    let node = parse_tree(bytes).map_err(|e| JsValue::from_str(&format!("Parse error: {}", e)))?;
+    let tree = CodeTree::from(node);
 
     serde_wasm_bindgen::to_value(&tree)
         .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
 }
 
+/// Parses every file (strings or `Uint8Array`s, gzip allowed) and merges the
+/// projects by path into one tree.
+#[wasm_bindgen]
+pub fn merge_cc_files(files: Vec<JsValue>) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+
+    let mut trees: Vec<Node> = Vec::with_capacity(files.len());
+    for (i, f) in files.iter().enumerate() {
+        let node = parse_tree(&js_bytes(f))
+            .map_err(|e| JsValue::from_str(&format!("Parse error in file {}: {}", i, e)))?;
+        trees.push(node);
+    }
+    let merged = merge_trees(trees).ok_or_else(|| JsValue::from_str("No files to merge"))?;
+
+    serde_wasm_bindgen::to_value(&CodeTree::from(merged))
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
+/// Compares two projects (strings or `Uint8Array`s, gzip allowed) and returns
+/// the tree of the second with a [`NodeDelta`] on every node, removed files
+/// included, so the view can color buildings by growth or shrinkage.
+#[wasm_bindgen]
+pub fn delta_cc_files(before: JsValue, after: JsValue) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+
+    let parse = |side: &str, value: &JsValue| {
+        parse_tree(&js_bytes(value))
+            .map_err(|e| JsValue::from_str(&format!("Parse error in {} file: {}", side, e)))
+    };
+    let delta = delta_tree(&parse("before", &before)?, &parse("after", &after)?);
+
+    serde_wasm_bindgen::to_value(&CodeTree::from(delta))
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
 #[wasm_bindgen]
 pub fn layout_treemap(tree_js: &JsValue, width: f32, height: f32) -> Result<JsValue, JsValue> {
     console_error_panic_hook::set_once();
@@ -89,6 +269,18 @@ pub fn get_language_colors() -> Result<JsValue, JsValue> {
     colors.insert("toml".to_string(), "#9c4221".to_string());
     colors.insert("xml".to_string(), "#9cdcfe".to_string());
     colors.insert("svelte".to_string(), "#ff3e00".to_string());
+    colors.insert("vue".to_string(), "#41b883".to_string());
+    colors.insert("lua".to_string(), "#000080".to_string());
+    colors.insert("perl".to_string(), "#0298c3".to_string());
+    colors.insert("zig".to_string(), "#ec915c".to_string());
+    colors.insert("elixir".to_string(), "#6e4a7e".to_string());
+    colors.insert("erlang".to_string(), "#B83998".to_string());
+    colors.insert("clojure".to_string(), "#db5855".to_string());
+    colors.insert("julia".to_string(), "#a270ba".to_string());
+    colors.insert("fsharp".to_string(), "#b845fc".to_string());
+    colors.insert("svg".to_string(), "#ff9900".to_string());
+    colors.insert("tex".to_string(), "#3D6117".to_string());
+    colors.insert("text".to_string(), "#bbbbbb".to_string());
     colors.insert("objective-c".to_string(), "#438eff".to_string());
     colors.insert("objective-cpp".to_string(), "#6866fb".to_string());
     colors.insert("ocaml".to_string(), "#ef7a00".to_string());
@@ -128,7 +320,8 @@ fn layout_node(
     depth: u32,
     rects: &mut Vec<RectNode>,
 ) {
-    if node.kind == "file" {
+    // Files and their functions are drawn; functions sit inside their file.
+    if node.kind == "file" || node.kind == "function" {
         rects.push(RectNode {
             path: node.path.clone(),
             x,
@@ -138,12 +331,21 @@ fn layout_node(
             depth,
             metrics: node.metrics.clone(),
             language: node.language.clone(),
+            attributes: node.attributes.clone(),
+            git: node.git.clone(),
+            hotspot_score: node.hotspot_score,
+            delta: node.delta.clone(),
+            function: node.function.clone(),
+            maintainability: node.maintainability.clone(),
         });
-        return;
     }
 
     if let Some(children) = &node.children {
-        let total_loc: u32 = children.iter().map(|child| child.metrics.loc).sum();
+        let mut total_loc: u32 = children.iter().map(|child| child.metrics.loc).sum();
+        // A file's functions leave room for the lines outside them.
+        if node.kind == "file" {
+            total_loc = total_loc.max(node.metrics.loc);
+        }
 
         if total_loc == 0 {
             return;
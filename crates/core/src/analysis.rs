@@ -0,0 +1,313 @@
+use crate::language::operator_set;
+use crate::metrics_calc::*;
+use crate::report::{Maintainability, Metrics};
+use crate::rust_ast;
+#[cfg(feature = "tree-sitter")]
+use crate::syntax_tree;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::ops::AddAssign;
+
+/// Tokens counted by the Rust audit.
+pub const AUDIT_TOKENS: &[&str] = &[
+    ".unwrap()",
+    ".expect(",
+    "panic!",
+    "todo!",
+    "dbg!",
+    "unimplemented!",
+    "assert!",
+    "assert_eq!",
+    "unsafe",
+    ".clone()",
+    "unwrap_or(",
+    "unwrap_or_else(",
+];
+
+/// Everything the scanner derives from one file's contents. This is the unit
+/// the scan cache stores.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FileAnalysis {
+    pub language: Option<String>,
+    pub metrics: Metrics,
+    /// tokei's line breakdown; `None` for files tokei does not recognize.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lines: Option<LineStats>,
+    /// What the language's parser found; `None` for languages without one
+    /// and Rust files that do not parse, which fall back to text heuristics.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub syntax: Option<SyntaxAnalysis>,
+    /// Halstead measures and maintainability index; `None` for languages
+    /// without an operator set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub maintainability: Option<Maintainability>,
+    /// The operators and operands behind `maintainability`, pooled over the
+    /// scan for `advanced.halstead`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub halstead: Option<HalsteadCounts>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rust: Option<RustAnalysis>,
+}
+
+/// Halstead operator and operand counts of one file.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct HalsteadCounts {
+    pub ops_total: usize,
+    pub operands_total: usize,
+    /// Distinct operators, sorted.
+    pub ops: Vec<String>,
+    /// Distinct operands, sorted.
+    pub operands: Vec<String>,
+}
+
+/// Line counts for one file, keyed by tokei's language name.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct LineStats {
+    pub language: String,
+    pub code: usize,
+    pub comments: usize,
+    pub blanks: usize,
+}
+
+/// Inputs to the project-wide Rust metrics (audit, ABC, cyclomatic).
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct RustAnalysis {
+    /// Empty when the file does not parse.
+    #[serde(default)]
+    pub items: RustItems,
+    /// Non-zero audit token counts.
+    pub audit: BTreeMap<String, usize>,
+    pub abc_a: usize,
+    pub abc_b: usize,
+    pub abc_c: usize,
+    pub cc_decisions: usize,
+}
+
+/// Item counts of a parsed Rust file, or summed over a scan.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct RustItems {
+    /// Free functions, including ones nested in other functions.
+    pub functions: usize,
+    /// Functions in `impl` and `trait` blocks, with or without a body.
+    pub methods: usize,
+    pub traits: usize,
+    pub impls: usize,
+}
+
+impl AddAssign<&RustItems> for RustItems {
+    fn add_assign(&mut self, rhs: &RustItems) {
+        self.functions += rhs.functions;
+        self.methods += rhs.methods;
+        self.traits += rhs.traits;
+        self.impls += rhs.impls;
+    }
+}
+
+/// Decision points, cognitive complexity and nesting from a file's syntax
+/// tree.
+///
+/// A decision point is a branch: `if`, a loop, a `case` or match arm beyond
+/// the default, a guard, a `catch`, a ternary, `&&` / `||` and a closure or
+/// lambda; Rust adds `?` and `let ... else`. Nesting counts the enclosing
+/// conditionals, loops, switches, `catch` blocks and closures; an `else if`
+/// stays at the level of its `if`.
+///
+/// Cognitive complexity follows SonarSource's definition: conditionals,
+/// loops, switches / `match`, `catch`, ternaries and `let ... else` add 1
+/// plus their nesting; `else`, `else if`, labeled jumps and every run of
+/// like `&&` / `||` operators add 1. Cases, `?` and closures add nothing,
+/// though closures nest.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct SyntaxAnalysis {
+    /// Decision points in the whole file, including those outside any
+    /// function.
+    pub decisions: usize,
+    /// Cognitive complexity of the whole file, including code outside any
+    /// function.
+    #[serde(default)]
+    pub cognitive: usize,
+    /// Every function with a body, in source order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub functions: Vec<FunctionAnalysis>,
+    /// The deepest nesting anywhere in the file.
+    pub max_nesting: usize,
+}
+
+/// One function, method or constructor. Nested functions are separate
+/// entries and do not add to the function around them.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FunctionAnalysis {
+    /// The function name, prefixed with its enclosing modules, namespaces,
+    /// classes or the type or trait of its `impl` (`scan::Walker::run`).
+    pub name: String,
+    /// 1-based line of the name.
+    pub line: usize,
+    /// 1-based line the body ends on.
+    pub end_line: usize,
+    /// Declared parameters, without `self` / `this`.
+    pub params: usize,
+    /// Cyclomatic complexity: 1 plus the decision points of the body.
+    pub complexity: usize,
+    #[serde(default)]
+    pub cognitive: usize,
+    /// The deepest nesting inside the body.
+    pub nesting: usize,
+}
+
+/// Analyzes `txt` as `lang`, a palette id such as `rust` (see
+/// [`crate::language::language_id`]).
+pub fn analyze_source(lang: Option<String>, txt: &str) -> FileAnalysis {
+    let loc = txt.lines().count();
+    let s = sanitize_language(lang.as_deref(), txt);
+    // Languages with a parser only fall back to text heuristics when there
+    // is no syntax tree.
+    let (syntax, rust_items) = match lang.as_deref() {
+        Some("rust") => rust_ast::analyze(txt).map_or((None, None), |(s, i)| (Some(s), Some(i))),
+        #[cfg(feature = "tree-sitter")]
+        Some(id) => (syntax_tree::analyze(id, txt), None),
+        _ => (None, None),
+    };
+    let brace_syntax = matches!(
+        lang.as_deref(),
+        Some(
+            "typescript"
+                | "javascript"
+                | "java"
+                | "cpp"
+                | "c"
+                | "csharp"
+                | "php"
+                | "kotlin"
+                | "swift"
+                | "scala"
+                | "go"
+        )
+    );
+    let complexity = match (lang.as_deref(), &syntax) {
+        (_, Some(syntax)) => syntax.decisions + 1,
+        (Some("rust"), None) => count_cyclomatic_decisions(&s) + 1,
+        (_, None) if brace_syntax => count_conditionals_js_like(&s) + 1,
+        (Some("python"), None) => count_conditionals_python(&s) + 1,
+        _ => 0,
+    };
+    let (cognitive, max_nesting) = match (lang.as_deref(), &syntax) {
+        (_, Some(syntax)) => (syntax.cognitive, syntax.max_nesting),
+        (Some("rust"), None) => cognitive_and_nesting(&s),
+        (_, None) if brace_syntax => cognitive_and_nesting(&s),
+        _ => (0, 0),
+    };
+    let functions = match (lang.as_deref(), &syntax) {
+        (_, Some(syntax)) => syntax.functions.len(),
+        (Some("rust"), None) => count_token(&s, "fn "),
+        (Some("typescript") | Some("javascript"), None) => {
+            count_token(&s, "function ") + count_token(&s, "=>")
+        }
+        (Some("python"), None) => count_token(&s, "def "),
+        (Some("go"), None) => count_token(&s, "func "),
+        // The tree-sitter languages only lack a syntax tree in builds
+        // without tree-sitter.
+        _ => 0,
+    };
+    // Halstead measures count the language's own operators, in any code
+    // file, branching or not.
+    let halstead = lang
+        .as_deref()
+        .and_then(operator_set)
+        .map(|set| halstead_collect(&s, set));
+    let maintainability = halstead
+        .as_ref()
+        .map(|h| maintainability(txt, &s, complexity, h));
+    let halstead = halstead.map(|(ops_total, operands_total, ops, operands)| {
+        let mut ops: Vec<String> = ops.into_iter().collect();
+        ops.sort();
+        let mut operands: Vec<String> = operands.into_iter().collect();
+        operands.sort();
+        HalsteadCounts {
+            ops_total,
+            operands_total,
+            ops,
+            operands,
+        }
+    });
+    let rust = (lang.as_deref() == Some("rust"))
+        .then(|| analyze_rust(&s, syntax.as_ref(), rust_items.unwrap_or_default()));
+    let blanks = txt.lines().filter(|l| l.trim().is_empty()).count();
+    FileAnalysis {
+        language: lang,
+        metrics: Metrics {
+            loc,
+            complexity,
+            functions,
+            lines: loc,
+            code: loc - blanks,
+            comments: 0,
+            blanks,
+            cognitive,
+            max_nesting,
+        },
+        lines: None,
+        syntax,
+        maintainability,
+        halstead,
+        rust,
+    }
+}
+
+type Halstead = (usize, usize, HashSet<String>, HashSet<String>);
+
+/// Halstead volume, difficulty and effort from [`halstead_collect`], and the
+/// maintainability index, 0 to 100, from the volume, `complexity`, the code
+/// lines of sanitized `s` and the share of other lines in `txt`.
+fn maintainability(txt: &str, s: &str, complexity: usize, halstead: &Halstead) -> Maintainability {
+    let (ops_total, operands_total, ops, operands) = halstead;
+    let distinct = ops.len() + operands.len();
+    let volume = if distinct > 0 {
+        ((ops_total + operands_total) as f64) * (distinct as f64).log2()
+    } else {
+        0.0
+    };
+    let difficulty = if operands.is_empty() {
+        0.0
+    } else {
+        (ops.len() as f64 / 2.0) * (*operands_total as f64 / operands.len() as f64)
+    };
+    let orig_lines = txt.lines().count();
+    let code_lines = s.lines().filter(|l| !l.trim().is_empty()).count();
+    let mi_raw = if code_lines > 0 && volume > 0.0 {
+        171.0 - 5.2 * volume.ln() - 0.23 * (complexity as f64) - 16.2 * (code_lines as f64).ln()
+    } else {
+        0.0
+    };
+    let cm_pct = if orig_lines > 0 {
+        ((orig_lines.saturating_sub(code_lines)) as f64) * 100.0 / (orig_lines as f64)
+    } else {
+        0.0
+    };
+    let mi = ((mi_raw * 100.0) / 171.0) + 50.0 * (2.4 * cm_pct).sqrt().sin();
+    Maintainability {
+        volume,
+        difficulty,
+        effort: volume * difficulty,
+        index: mi.clamp(0.0, 100.0),
+    }
+}
+
+fn analyze_rust(s: &str, syntax: Option<&SyntaxAnalysis>, items: RustItems) -> RustAnalysis {
+    let mut audit = BTreeMap::new();
+    for t in AUDIT_TOKENS {
+        let c = count_token(s, t);
+        if c > 0 {
+            audit.insert(t.to_string(), c);
+        }
+    }
+    let cc_decisions =
+        syntax.map_or_else(|| count_cyclomatic_decisions(s), |syntax| syntax.decisions);
+    RustAnalysis {
+        items,
+        audit,
+        abc_a: count_assignments(s),
+        abc_b: count_branches(s),
+        abc_c: count_conditionals(s),
+        cc_decisions,
+    }
+}
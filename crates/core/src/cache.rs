@@ -0,0 +1,239 @@
+//! On-disk cache of per-file analysis results, so a rescan only re-reads and
+//! re-analyzes files that changed.
+//!
+//! Entries are keyed by path relative to the scan root. A matching mtime and
+//! size skips the read entirely; otherwise the file is read and its SHA-256
+//! compared, so touched-but-unchanged files are not re-analyzed either. The
+//! whole cache is discarded when the tool version, the cache schema or the
+//! scan configuration changes.
+
+use crate::analysis::FileAnalysis;
+use crate::config::ScanConfig;
+use crate::report::CacheStats;
+use crate::scan::analyze_file;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+/// File name of the cache inside `metrics_dir`.
+pub const CACHE_FILE: &str = "ryoiki.cache.json";
+
+/// Bumped whenever [`FileAnalysis`] or the analysis itself changes meaning.
+const CACHE_SCHEMA: u32 = 17;
+
+#[derive(Serialize, Deserialize)]
+struct CacheFile {
+    fingerprint: String,
+    entries: HashMap<String, CacheEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct CacheEntry {
+    mtime_ns: u128,
+    size: u64,
+    sha256: String,
+    analysis: FileAnalysis,
+}
+
+/// Lookups take `&self` so walker threads can share the cache; results are
+/// committed afterwards with [`ScanCache::record`].
+pub struct ScanCache {
+    fingerprint: String,
+    previous: HashMap<String, CacheEntry>,
+    current: HashMap<String, CacheEntry>,
+    stats: CacheStats,
+}
+
+/// The result of [`ScanCache::analyze`], to be passed to [`ScanCache::record`].
+pub struct CachedAnalysis {
+    entry: CacheEntry,
+    hit: bool,
+}
+
+impl CachedAnalysis {
+    pub fn analysis(&self) -> &FileAnalysis {
+        &self.entry.analysis
+    }
+}
+
+impl ScanCache {
+    /// An empty cache for `cfg`.
+    pub fn new(cfg: &ScanConfig) -> Self {
+        ScanCache {
+            fingerprint: fingerprint(cfg),
+            previous: HashMap::new(),
+            current: HashMap::new(),
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// Loads the cache at `path`. A missing, unreadable or stale cache yields
+    /// an empty one.
+    pub fn load(path: &Path, cfg: &ScanConfig) -> Self {
+        let mut cache = ScanCache::new(cfg);
+        let stored = fs::read(path)
+            .ok()
+            .and_then(|b| serde_json::from_slice::<CacheFile>(&b).ok());
+        if let Some(stored) = stored
+            && stored.fingerprint == cache.fingerprint
+        {
+            cache.previous = stored.entries;
+        }
+        cache
+    }
+
+    /// Writes the entries recorded by this scan; files that disappeared are
+    /// dropped.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let file = CacheFile {
+            fingerprint: self.fingerprint.clone(),
+            entries: self.current.clone(),
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_vec(&file)?)
+    }
+
+    pub fn stats(&self) -> &CacheStats {
+        &self.stats
+    }
+
+    /// Analyzes `abs`, stored under `rel`, reusing the previous scan's result
+    /// when the file is unchanged.
+    pub fn analyze(&self, rel: &str, abs: &Path) -> io::Result<CachedAnalysis> {
+        let meta = fs::metadata(abs)?;
+        let size = meta.len();
+        let mtime_ns = meta
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_nanos())
+            .unwrap_or(0);
+        let prev = self.previous.get(rel);
+        if let Some(e) = prev
+            && e.mtime_ns == mtime_ns
+            && e.size == size
+            && mtime_ns != 0
+        {
+            return Ok(CachedAnalysis {
+                entry: e.clone(),
+                hit: true,
+            });
+        }
+        let bytes = fs::read(abs)?;
+        self.analyze_contents(rel, abs, &bytes, mtime_ns)
+    }
+
+    /// Analyzes `bytes`, the contents of `rel` at some git revision. Blobs
+    /// have no mtime, so only the content hash can match, and the language
+    /// comes from `rel` and `bytes` alone, never from the working tree.
+    pub fn analyze_blob(&self, rel: &str, bytes: &[u8]) -> io::Result<CachedAnalysis> {
+        self.analyze_contents(rel, Path::new(rel), bytes, 0)
+    }
+
+    fn analyze_contents(
+        &self,
+        rel: &str,
+        path: &Path,
+        bytes: &[u8],
+        mtime_ns: u128,
+    ) -> io::Result<CachedAnalysis> {
+        let sha256 = format!("{:x}", Sha256::digest(bytes));
+        let (analysis, hit) = match self.previous.get(rel) {
+            Some(e) if e.sha256 == sha256 => (e.analysis.clone(), true),
+            _ => (analyze_file(path, bytes)?, false),
+        };
+        Ok(CachedAnalysis {
+            entry: CacheEntry {
+                mtime_ns,
+                size: bytes.len() as u64,
+                sha256,
+                analysis,
+            },
+            hit,
+        })
+    }
+
+    /// Makes the entries recorded so far reusable by the next scan through
+    /// this same cache, and resets the statistics. Earlier entries stay, so a
+    /// file that comes back in a later revision is still found.
+    pub(crate) fn carry_over(&mut self) {
+        self.previous.extend(self.current.drain());
+        self.stats = CacheStats::default();
+    }
+
+    /// Keeps `c` for the next scan and counts it as a hit or miss.
+    pub fn record(&mut self, rel: &str, c: CachedAnalysis) {
+        if c.hit {
+            self.stats.hits += 1;
+        } else {
+            self.stats.misses += 1;
+        }
+        self.current.insert(rel.to_string(), c.entry);
+    }
+}
+
+fn fingerprint(cfg: &ScanConfig) -> String {
+    let mut h = Sha256::new();
+    h.update(env!("CARGO_PKG_VERSION"));
+    h.update(CACHE_SCHEMA.to_le_bytes());
+    // Without tree-sitter, languages other than Rust fall back to text
+    // heuristics and have no syntax tree.
+    h.update([u8::from(cfg!(feature = "tree-sitter"))]);
+    for e in &cfg.excludes {
+        h.update([0]);
+        h.update(e);
+    }
+    for i in &cfg.includes {
+        h.update([1]);
+        h.update(i);
+    }
+    h.update(format!(
+        "{}:{}:{:?}",
+        cfg.hidden, cfg.follow_symlinks, cfg.max_file_size
+    ));
+    format!("{:x}", h.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_reuses_until_content_or_config_changes() {
+        let dir = std::env::temp_dir().join(format!("ryoiki-cache-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("create temp dir");
+        let file = dir.join("a.rs");
+        let cache_path = dir.join(CACHE_FILE);
+        fs::write(&file, "fn a() {}\n").expect("write source");
+        let cfg = ScanConfig::new(&dir);
+
+        let scan = |cfg: &ScanConfig| {
+            let mut cache = ScanCache::load(&cache_path, cfg);
+            let c = cache.analyze("a.rs", &file).expect("analyze");
+            let functions = c.analysis().metrics.functions;
+            cache.record("a.rs", c);
+            cache.save(&cache_path).expect("save");
+            (functions, cache.stats().clone())
+        };
+        let miss = CacheStats { hits: 0, misses: 1 };
+
+        assert_eq!(scan(&cfg), (1, miss.clone()));
+        assert_eq!(scan(&cfg), (1, CacheStats { hits: 1, misses: 0 }));
+
+        fs::write(&file, "fn a() {}\nfn b() {}\n").expect("rewrite source");
+        assert_eq!(scan(&cfg), (2, miss.clone()));
+
+        let mut other = cfg.clone();
+        other.excludes.push("vendor".to_string());
+        assert_eq!(scan(&other), (2, miss));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,582 @@
+//! Export to CodeCharta's `cc.json` format (API version 1.3).
+//!
+//! `ryoiki.cc.json` keeps ryoiki's own tree shape for the bundled viewer;
+//! this module produces the file CodeCharta's web studio and `ccsh` load.
+
+use crate::language::{dominant_language, language_from_path};
+use crate::report::{Metrics, Node};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+pub const API_VERSION: &str = "1.3";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CcProject {
+    pub project_name: String,
+    pub api_version: String,
+    pub nodes: Vec<CcNode>,
+    /// Optional in the format; older API versions and many `ccsh` outputs
+    /// leave it out.
+    #[serde(default)]
+    pub attribute_types: CcAttributeTypes,
+    #[serde(default)]
+    pub attribute_descriptors: BTreeMap<String, CcAttributeDescriptor>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub edges: Vec<CcEdge>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blacklist: Vec<CcBlacklistItem>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CcNodeType {
+    File,
+    Folder,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CcNode {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: CcNodeType,
+    #[serde(default)]
+    pub attributes: BTreeMap<String, serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<CcNode>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CcAttributeType {
+    Absolute,
+    Relative,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CcAttributeTypes {
+    #[serde(default)]
+    pub nodes: BTreeMap<String, CcAttributeType>,
+    #[serde(default)]
+    pub edges: BTreeMap<String, CcAttributeType>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CcAttributeDescriptor {
+    pub title: String,
+    pub description: String,
+    pub hint_low_value: String,
+    pub hint_high_value: String,
+    pub link: String,
+    /// `-1` when lower values are better, `1` when higher values are better.
+    pub direction: i8,
+}
+
+/// A dependency between two nodes, addressed by CodeCharta path
+/// (`/root/src/main.rs`).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CcEdge {
+    pub from_node_name: String,
+    pub to_node_name: String,
+    pub attributes: BTreeMap<String, f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CcBlacklistType {
+    Flatten,
+    Exclude,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CcBlacklistItem {
+    pub path: String,
+    #[serde(rename = "type")]
+    pub kind: CcBlacklistType,
+}
+
+/// Converts a ryoiki tree into a CodeCharta project. The root is renamed to
+/// `root` as CodeCharta requires. The tree's coupling edges become `edges`;
+/// `blacklist` starts empty for the caller to fill in.
+pub fn to_codecharta(tree: &Node, project_name: &str) -> CcProject {
+    let mut root = convert_node(tree);
+    root.name = "root".to_string();
+
+    let mut attribute_types = CcAttributeTypes::default();
+    let mut attribute_descriptors = BTreeMap::new();
+    let git_attributes = if tree.git.is_some() {
+        GIT_ATTRIBUTES
+    } else {
+        &[]
+    };
+    let maintainability_attributes = if tree.maintainability.is_some() {
+        MAINTAINABILITY_ATTRIBUTES
+    } else {
+        &[]
+    };
+    for (key, title, description, direction) in NODE_ATTRIBUTES
+        .iter()
+        .chain(git_attributes)
+        .chain(maintainability_attributes)
+    {
+        attribute_types
+            .nodes
+            .insert(key.to_string(), CcAttributeType::Absolute);
+        attribute_descriptors.insert(
+            key.to_string(),
+            CcAttributeDescriptor {
+                title: title.to_string(),
+                description: description.to_string(),
+                hint_low_value: String::new(),
+                hint_high_value: String::new(),
+                link: String::new(),
+                direction: *direction,
+            },
+        );
+    }
+    // A folder holds the deepest nesting below it, not a sum, and the
+    // combined difficulty and index of its files.
+    attribute_types
+        .nodes
+        .insert("max_nesting_depth".to_string(), CcAttributeType::Relative);
+    if !maintainability_attributes.is_empty() {
+        for key in ["halstead_difficulty", "maintainability_index"] {
+            attribute_types
+                .nodes
+                .insert(key.to_string(), CcAttributeType::Relative);
+        }
+    }
+
+    let edges = convert_edges(tree, &mut attribute_types);
+    CcProject {
+        project_name: project_name.to_string(),
+        api_version: API_VERSION.to_string(),
+        nodes: vec![root],
+        attribute_types,
+        attribute_descriptors,
+        edges,
+        blacklist: Vec::new(),
+    }
+}
+
+fn convert_edges(tree: &Node, types: &mut CcAttributeTypes) -> Vec<CcEdge> {
+    if tree.edges.is_empty() {
+        return Vec::new();
+    }
+    types
+        .edges
+        .insert("support".to_string(), CcAttributeType::Absolute);
+    for key in ["confidence", "degree"] {
+        types
+            .edges
+            .insert(key.to_string(), CcAttributeType::Relative);
+    }
+    let node_name = |path: &str| format!("/root/{}", path.replace('\\', "/"));
+    tree.edges
+        .iter()
+        .map(|e| CcEdge {
+            from_node_name: node_name(&e.from),
+            to_node_name: node_name(&e.to),
+            attributes: BTreeMap::from([
+                ("support".to_string(), e.support as f64),
+                ("confidence".to_string(), e.confidence),
+                ("degree".to_string(), e.degree),
+            ]),
+        })
+        .collect()
+}
+
+/// `(key, title, description, direction)` for every node attribute ryoiki emits.
+const NODE_ATTRIBUTES: &[(&str, &str, &str, i8)] = &[
+    (
+        "loc",
+        "Lines of Code",
+        "Physical lines, including blanks and comments",
+        -1,
+    ),
+    (
+        "complexity",
+        "Cyclomatic Complexity",
+        "Decision points plus one per file",
+        -1,
+    ),
+    (
+        "functions",
+        "Functions",
+        "Number of function definitions",
+        -1,
+    ),
+    (
+        "rloc",
+        "Real Lines of Code",
+        "Lines containing code, as counted by tokei",
+        -1,
+    ),
+    (
+        "comment_lines",
+        "Comment Lines",
+        "Lines containing only comments",
+        1,
+    ),
+    ("empty_lines", "Empty Lines", "Blank lines", -1),
+    (
+        "cognitive_complexity",
+        "Cognitive Complexity",
+        "SonarSource cognitive complexity",
+        -1,
+    ),
+    (
+        "max_nesting_depth",
+        "Maximum Nesting Depth",
+        "Deepest nesting of conditionals and loops",
+        -1,
+    ),
+];
+
+/// Emitted when the tree carries Halstead measures.
+const MAINTAINABILITY_ATTRIBUTES: &[(&str, &str, &str, i8)] = &[
+    (
+        "halstead_volume",
+        "Halstead Volume",
+        "Operators and operands used, times log2 of the distinct ones",
+        -1,
+    ),
+    (
+        "halstead_difficulty",
+        "Halstead Difficulty",
+        "Half the distinct operators times the average uses of an operand",
+        -1,
+    ),
+    (
+        "halstead_effort",
+        "Halstead Effort",
+        "Volume times difficulty",
+        -1,
+    ),
+    (
+        "maintainability_index",
+        "Maintainability Index",
+        "0 to 100; higher is easier to maintain",
+        1,
+    ),
+];
+
+/// Emitted when the tree carries git history, under the names CodeCharta's
+/// git log parser uses.
+const GIT_ATTRIBUTES: &[(&str, &str, &str, i8)] = &[
+    (
+        "number_of_commits",
+        "Number of Commits",
+        "Non-merge commits in the history window",
+        -1,
+    ),
+    (
+        "number_of_authors",
+        "Number of Authors",
+        "Distinct authors in the history window",
+        -1,
+    ),
+    (
+        "added_lines",
+        "Added Lines",
+        "Lines added by those commits",
+        -1,
+    ),
+    (
+        "deleted_lines",
+        "Deleted Lines",
+        "Lines deleted by those commits",
+        -1,
+    ),
+];
+
+fn convert_node(node: &Node) -> CcNode {
+    let mut attributes: BTreeMap<String, serde_json::Value> = node
+        .attributes
+        .iter()
+        .map(|(k, v)| (k.clone(), (*v).into()))
+        .collect();
+    attributes.insert("loc".to_string(), node.metrics.loc.into());
+    attributes.insert("complexity".to_string(), node.metrics.complexity.into());
+    attributes.insert("functions".to_string(), node.metrics.functions.into());
+    attributes.insert("rloc".to_string(), node.metrics.code.into());
+    attributes.insert("comment_lines".to_string(), node.metrics.comments.into());
+    attributes.insert("empty_lines".to_string(), node.metrics.blanks.into());
+    attributes.insert(
+        "cognitive_complexity".to_string(),
+        node.metrics.cognitive.into(),
+    );
+    attributes.insert(
+        "max_nesting_depth".to_string(),
+        node.metrics.max_nesting.into(),
+    );
+    if let Some(git) = &node.git {
+        attributes.insert("number_of_commits".to_string(), git.commits.into());
+        attributes.insert("number_of_authors".to_string(), git.authors.into());
+        attributes.insert("added_lines".to_string(), git.lines_added.into());
+        attributes.insert("deleted_lines".to_string(), git.lines_deleted.into());
+    }
+    if let Some(m) = &node.maintainability {
+        attributes.insert("halstead_volume".to_string(), m.volume.into());
+        attributes.insert("halstead_difficulty".to_string(), m.difficulty.into());
+        attributes.insert("halstead_effort".to_string(), m.effort.into());
+        attributes.insert("maintainability_index".to_string(), m.index.into());
+    }
+    // CodeCharta files cannot hold nodes; function nodes are left out.
+    match node.entries() {
+        Some(children) => CcNode {
+            name: node.name.clone(),
+            kind: CcNodeType::Folder,
+            attributes,
+            link: None,
+            children: children.iter().map(convert_node).collect(),
+        },
+        None => CcNode {
+            name: node.name.clone(),
+            kind: CcNodeType::File,
+            attributes,
+            link: None,
+            children: Vec::new(),
+        },
+    }
+}
+
+/// Attribute names other tools use for ryoiki's core metrics, in order of
+/// preference.
+const LOC_KEYS: &[&str] = &["loc", "rloc", "lines_of_code"];
+const COMPLEXITY_KEYS: &[&str] = &["complexity", "mcc", "sonar_complexity"];
+const FUNCTION_KEYS: &[&str] = &["functions", "number_of_functions"];
+const CODE_KEYS: &[&str] = &["rloc"];
+const COMMENT_KEYS: &[&str] = &["comment_lines"];
+const BLANK_KEYS: &[&str] = &["empty_lines"];
+const COGNITIVE_KEYS: &[&str] = &["cognitive_complexity"];
+const NESTING_KEYS: &[&str] = &["max_nesting_depth"];
+
+/// Attributes that map onto [`Metrics`] fields and are not kept as extras.
+const METRIC_KEYS: &[&str] = &[
+    "loc",
+    "complexity",
+    "functions",
+    "rloc",
+    "comment_lines",
+    "empty_lines",
+    "cognitive_complexity",
+    "max_nesting_depth",
+];
+
+/// Converts a CodeCharta project back into a ryoiki tree. Attributes that do
+/// not map onto [`Metrics`] are kept in [`Node::attributes`];
+/// folders without their own value get the sum of their children for
+/// attributes typed `absolute` (the CodeCharta default).
+pub fn from_codecharta(project: &CcProject) -> Option<Node> {
+    let root = project.nodes.first()?;
+    let mut node = import_node(root, "", &project.attribute_types);
+    node.name = project.project_name.clone();
+    node.path = ".".to_string();
+    Some(node)
+}
+
+fn import_node(cc: &CcNode, path: &str, types: &CcAttributeTypes) -> Node {
+    let child_path = |name: &str| {
+        if path.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}/{}", path, name)
+        }
+    };
+    let mut attributes: BTreeMap<String, f64> = cc
+        .attributes
+        .iter()
+        .filter_map(|(k, v)| v.as_f64().map(|f| (k.clone(), f)))
+        .collect();
+    let pick = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|k| attributes.get(*k))
+            .map(|v| v.max(0.0).round() as usize)
+    };
+
+    match cc.kind {
+        CcNodeType::File => {
+            let loc = pick(LOC_KEYS).unwrap_or(0);
+            let metrics = Metrics {
+                loc,
+                complexity: pick(COMPLEXITY_KEYS).unwrap_or(0),
+                functions: pick(FUNCTION_KEYS).unwrap_or(0),
+                lines: loc,
+                code: pick(CODE_KEYS).unwrap_or(loc),
+                comments: pick(COMMENT_KEYS).unwrap_or(0),
+                blanks: pick(BLANK_KEYS).unwrap_or(0),
+                cognitive: pick(COGNITIVE_KEYS).unwrap_or(0),
+                max_nesting: pick(NESTING_KEYS).unwrap_or(0),
+            };
+            for k in METRIC_KEYS {
+                attributes.remove(*k);
+            }
+            Node {
+                name: cc.name.clone(),
+                language: language_from_path(Path::new(&cc.name)),
+                attributes,
+                ..Node::file(path, metrics)
+            }
+        }
+        CcNodeType::Folder => {
+            let children: Vec<Node> = cc
+                .children
+                .iter()
+                .map(|c| import_node(c, &child_path(&c.name), types))
+                .collect();
+            let mut langs: HashMap<String, usize> = HashMap::new();
+            let mut sums: BTreeMap<String, f64> = BTreeMap::new();
+            for c in &children {
+                if let Some(l) = &c.language {
+                    *langs.entry(l.clone()).or_default() += c.metrics.loc;
+                }
+                for (k, v) in &c.attributes {
+                    if types.nodes.get(k) != Some(&CcAttributeType::Relative) {
+                        *sums.entry(k.clone()).or_default() += v;
+                    }
+                }
+            }
+            for k in METRIC_KEYS {
+                attributes.remove(*k);
+            }
+            for (k, v) in sums {
+                attributes.entry(k).or_insert(v);
+            }
+            Node {
+                name: cc.name.clone(),
+                language: dominant_language(&langs),
+                attributes,
+                ..Node::directory(path, children)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::{Maintainability, Metrics};
+
+    fn file(name: &str, loc: usize) -> Node {
+        Node {
+            language: Some("rust".to_string()),
+            ..Node::file(
+                &format!("src/{}", name),
+                Metrics {
+                    loc,
+                    complexity: 2,
+                    functions: 1,
+                    ..Metrics::default()
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn test_to_codecharta_shape() {
+        let tree = Node {
+            name: "proj".to_string(),
+            language: Some("rust".to_string()),
+            ..Node::directory(".", vec![file("a.rs", 10), file("b.rs", 20)])
+        };
+        let v = serde_json::to_value(to_codecharta(&tree, "proj")).expect("serialize");
+
+        assert_eq!(v["projectName"], "proj");
+        assert_eq!(v["apiVersion"], "1.3");
+        assert_eq!(v["nodes"][0]["name"], "root");
+        assert_eq!(v["nodes"][0]["type"], "Folder");
+        assert_eq!(v["nodes"][0]["children"][1]["type"], "File");
+        assert_eq!(v["nodes"][0]["children"][1]["attributes"]["loc"], 20);
+        assert_eq!(v["attributeTypes"]["nodes"]["loc"], "absolute");
+        assert_eq!(v["attributeTypes"]["nodes"]["rloc"], "absolute");
+        assert_eq!(v["attributeDescriptors"]["loc"]["direction"], -1);
+        assert!(v.get("edges").is_none());
+        assert!(
+            v["attributeTypes"]["nodes"]
+                .get("halstead_volume")
+                .is_none()
+        );
+
+        let mut tree = tree;
+        tree.maintainability = Some(Maintainability {
+            volume: 120.0,
+            difficulty: 4.0,
+            effort: 480.0,
+            index: 72.5,
+        });
+        let v = serde_json::to_value(to_codecharta(&tree, "proj")).expect("serialize");
+        assert_eq!(v["nodes"][0]["attributes"]["maintainability_index"], 72.5);
+        assert_eq!(v["nodes"][0]["attributes"]["halstead_effort"], 480.0);
+        assert_eq!(v["attributeTypes"]["nodes"]["halstead_volume"], "absolute");
+        assert_eq!(
+            v["attributeTypes"]["nodes"]["maintainability_index"],
+            "relative"
+        );
+        assert_eq!(
+            v["attributeDescriptors"]["maintainability_index"]["direction"],
+            1
+        );
+    }
+
+    #[test]
+    fn test_from_codecharta_keeps_foreign_attributes() {
+        let json = r#"{
+            "projectName": "sonar",
+            "apiVersion": "1.3",
+            "nodes": [{
+                "name": "root",
+                "type": "Folder",
+                "attributes": {},
+                "children": [{
+                    "name": "src",
+                    "type": "Folder",
+                    "attributes": {},
+                    "children": [
+                        {"name": "a.rs", "type": "File",
+                         "attributes": {"rloc": 12, "mcc": 3, "code_smell": 2, "coverage": 80}},
+                        {"name": "b.py", "type": "File",
+                         "attributes": {"rloc": 4, "code_smell": 1, "coverage": 40}}
+                    ]
+                }]
+            }],
+            "attributeTypes": {"nodes": {"coverage": "relative"}, "edges": {}}
+        }"#;
+        let project: CcProject = serde_json::from_str(json).expect("parse");
+        let tree = from_codecharta(&project).expect("root node");
+
+        assert_eq!(tree.name, "sonar");
+        assert_eq!(tree.path, ".");
+        assert_eq!(tree.metrics.loc, 16);
+        assert_eq!(tree.metrics.code, 16);
+        let src = &tree.children.as_ref().expect("children")[0];
+        assert_eq!(src.attributes.get("code_smell"), Some(&3.0));
+        assert_eq!(src.attributes.get("coverage"), None);
+        let a = &src.children.as_ref().expect("children")[0];
+        assert_eq!(a.path, "src/a.rs");
+        assert_eq!(a.kind, "file");
+        assert_eq!(a.metrics.complexity, 3);
+        assert_eq!(a.language.as_deref(), Some("rust"));
+        assert_eq!(a.attributes.get("coverage"), Some(&80.0));
+        assert_eq!(a.attributes.get("rloc"), None);
+        assert_eq!(src.language.as_deref(), Some("rust"));
+    }
+
+    #[test]
+    fn test_from_codecharta_without_attribute_types() {
+        let json = r#"{
+            "projectName": "old",
+            "apiVersion": "1.0",
+            "nodes": [{"name": "root", "type": "Folder", "children": [
+                {"name": "a.rs", "type": "File", "attributes": {"rloc": 5}}
+            ]}]
+        }"#;
+        let project: CcProject = serde_json::from_str(json).expect("parse");
+        let tree = from_codecharta(&project).expect("tree");
+        assert_eq!(tree.metrics.loc, 5);
+    }
+}
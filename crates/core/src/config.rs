@@ -0,0 +1,561 @@
+//! Scan configuration.
+//!
+//! Values are layered, highest precedence first: command-line overrides,
+//! environment variables, the project file (`tools.config.toml` or
+//! `tools.config.json`), then the built-in defaults.
+
+use crate::snapshots::SNAPSHOT_DIR;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+
+/// Patterns that are never scanned. Like every exclude, a bare name matches a
+/// file or directory of that name at any depth.
+pub const DEFAULT_EXCLUDES: &[&str] = &[
+    "target",
+    "node_modules",
+    "dist",
+    "build",
+    "npm_modules",
+    ".git",
+    "ryoiki.cc.json",
+    "ryoiki.metrics.json",
+    "ryoiki.codecharta.cc.json",
+    "ryoiki.cache.json",
+    "package-lock.json",
+];
+
+/// Project files looked up in the project root, in order; the first one
+/// that exists is used.
+pub const CONFIG_FILES: &[&str] = &["tools.config.toml", "tools.config.json"];
+
+const DEFAULT_AUDIT_DIR: &str = ".";
+const DEFAULT_METRICS_DIR: &str = "tools/metrics";
+/// Length of the hotspot list in `ryoiki.metrics.json` unless configured.
+pub const DEFAULT_HOTSPOT_TOP: usize = 10;
+/// How far back the history goes unless configured, so a scan of a long-lived
+/// repository does not replay every commit.
+pub const DEFAULT_HISTORY_DAYS: u64 = 365;
+/// Snapshots kept by [`crate::SnapshotStore::save`] unless configured.
+pub const DEFAULT_SNAPSHOT_KEEP: usize = 100;
+
+/// What to scan and where [`crate::write_outputs`] should put the results.
+#[derive(Serialize, Debug, Clone)]
+pub struct ScanConfig {
+    pub root: PathBuf,
+    /// Gitignore-style globs, relative to `root`. When non-empty, only files
+    /// matching one of them are scanned.
+    pub includes: Vec<String>,
+    /// Gitignore-style globs, relative to `root`, that are skipped. They win
+    /// over `includes`.
+    pub excludes: Vec<String>,
+    /// Scan hidden (dot) files and directories such as `.github`.
+    pub hidden: bool,
+    pub follow_symlinks: bool,
+    /// Files larger than this many bytes are skipped.
+    pub max_file_size: Option<u64>,
+    /// How deep the tree goes.
+    pub depth: ScanDepth,
+    pub outputs: ScanOutputs,
+    /// Per-file analysis cache; `None` re-analyzes every file.
+    pub cache_path: Option<PathBuf>,
+    /// Commit, tag or branch to scan. Its files are read from the git object
+    /// database instead of the working tree; `None` scans the working tree.
+    pub revision: Option<String>,
+    /// Git history to attach to the tree; `None` skips reading the
+    /// repository.
+    pub history: Option<HistoryOptions>,
+    /// How many files [`crate::ScanReport::hotspots`] lists.
+    pub hotspot_top: usize,
+    /// Thresholds for the coupling edges on the root node; `None` skips
+    /// them. Needs `history`.
+    pub coupling: Option<CouplingOptions>,
+    /// Retention of the scan snapshots kept in the metrics directory; `None`
+    /// stores none.
+    pub snapshots: Option<SnapshotOptions>,
+    /// The project file the settings were read from, if any.
+    pub config_file: Option<PathBuf>,
+}
+
+/// The lowest level of the scan tree.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ScanDepth {
+    /// Files are leaves.
+    #[default]
+    Files,
+    /// Files hold a node per function and method their syntax tree shows;
+    /// files without one (see [`crate::analysis::FileAnalysis::syntax`])
+    /// stay leaves.
+    Functions,
+}
+
+impl FromStr for ScanDepth {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "files" => Ok(ScanDepth::Files),
+            "functions" => Ok(ScanDepth::Functions),
+            _ => Err(format!("expected files or functions, got {:?}", s)),
+        }
+    }
+}
+
+/// Which commits [`crate::read_history`] looks at.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct HistoryOptions {
+    /// Only commits at most this many days older than `HEAD`; `None` reads
+    /// the whole history.
+    pub since_days: Option<u64>,
+}
+
+/// Destinations for `ryoiki.cc.json` and `ryoiki.metrics.json`.
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct ScanOutputs {
+    pub metrics_dir: Option<PathBuf>,
+    pub web_dir: Option<PathBuf>,
+}
+
+/// Which co-changing file pairs become [`crate::CouplingEdge`]s.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct CouplingOptions {
+    /// Minimum number of shared commits.
+    pub min_support: usize,
+    /// Minimum degree of coupling, in percent.
+    pub min_degree: f64,
+    /// Commits touching more files than this are ignored.
+    pub max_changeset_size: usize,
+}
+
+impl Default for CouplingOptions {
+    fn default() -> Self {
+        CouplingOptions {
+            min_support: 3,
+            min_degree: 30.0,
+            max_changeset_size: 30,
+        }
+    }
+}
+
+/// How many snapshots [`crate::SnapshotStore::save`] keeps. Both limits apply.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SnapshotOptions {
+    /// The newest this many are kept; `None` keeps any number.
+    pub keep: Option<usize>,
+    /// Snapshots more than this many days older than the newest one are
+    /// dropped; `None` keeps them regardless of age.
+    pub max_age_days: Option<u64>,
+}
+
+impl Default for SnapshotOptions {
+    fn default() -> Self {
+        SnapshotOptions {
+            keep: Some(DEFAULT_SNAPSHOT_KEEP),
+            max_age_days: None,
+        }
+    }
+}
+
+/// The contents of `tools.config.toml` / `tools.config.json`. Unknown keys
+/// are rejected so typos do not go unnoticed.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ToolsConfig {
+    #[serde(default)]
+    pub paths: PathsConfig,
+    /// Older top-level alias for `paths.audit_dir`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audit_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub scan: ScanOptions,
+    #[serde(default)]
+    pub history: HistoryConfig,
+    #[serde(default)]
+    pub hotspots: HotspotsConfig,
+    #[serde(default)]
+    pub coupling: CouplingConfig,
+    #[serde(default)]
+    pub snapshots: SnapshotsConfig,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct PathsConfig {
+    /// Scan root, relative to the project root.
+    pub audit_dir: Option<PathBuf>,
+    /// Output directory, relative to the scan root.
+    pub metrics_dir: Option<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ScanOptions {
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Added to [`DEFAULT_EXCLUDES`].
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    pub hidden: Option<bool>,
+    pub follow_symlinks: Option<bool>,
+    pub max_file_size: Option<u64>,
+    /// Defaults to [`ScanDepth::Files`].
+    pub depth: Option<ScanDepth>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct HistoryConfig {
+    /// Read git history at all. Defaults to `true`.
+    pub enabled: Option<bool>,
+    /// Defaults to [`DEFAULT_HISTORY_DAYS`].
+    pub since_days: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct HotspotsConfig {
+    /// Defaults to [`DEFAULT_HOTSPOT_TOP`].
+    pub top: Option<usize>,
+}
+
+/// Unset values fall back to [`CouplingOptions::default`].
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct CouplingConfig {
+    /// Compute coupling edges at all. Defaults to `true`.
+    pub enabled: Option<bool>,
+    pub min_support: Option<usize>,
+    pub min_degree: Option<f64>,
+    pub max_changeset_size: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct SnapshotsConfig {
+    /// Store a snapshot per scan at all. Defaults to `true`.
+    pub enabled: Option<bool>,
+    /// Defaults to [`DEFAULT_SNAPSHOT_KEEP`].
+    pub keep: Option<usize>,
+    pub max_age_days: Option<u64>,
+}
+
+/// Values given on the command line. They win over every other layer.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigOverrides {
+    pub audit_dir: Option<PathBuf>,
+    pub metrics_dir: Option<PathBuf>,
+}
+
+impl ConfigOverrides {
+    /// Overrides only the scan root, as the `DIR` argument of most commands does.
+    pub fn for_dir(dir: Option<&Path>) -> Self {
+        ConfigOverrides {
+            audit_dir: dir.map(Path::to_path_buf),
+            metrics_dir: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read(PathBuf, io::Error),
+    Parse(PathBuf, String),
+    Invalid(PathBuf, String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read(p, e) => write!(f, "cannot read {}: {}", p.display(), e),
+            ConfigError::Parse(p, e) => write!(f, "invalid {}: {}", p.display(), e),
+            ConfigError::Invalid(p, e) => write!(f, "invalid {}: {}", p.display(), e),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl ToolsConfig {
+    /// Parses `text` as TOML when `path` ends in `.toml`, JSON otherwise.
+    pub fn parse(path: &Path, text: &str) -> Result<Self, ConfigError> {
+        let parsed: ToolsConfig = if path.extension().is_some_and(|e| e == "toml") {
+            toml::from_str(text)
+                .map_err(|e| ConfigError::Parse(path.to_path_buf(), e.to_string()))?
+        } else {
+            serde_json::from_str(text)
+                .map_err(|e| ConfigError::Parse(path.to_path_buf(), e.to_string()))?
+        };
+        parsed.validate(path)?;
+        Ok(parsed)
+    }
+
+    /// Reads the first of [`CONFIG_FILES`] found in `project_root`. No file
+    /// at all is not an error; the defaults apply.
+    pub fn load(project_root: &Path) -> Result<(Self, Option<PathBuf>), ConfigError> {
+        for name in CONFIG_FILES {
+            let path = project_root.join(name);
+            match fs::read_to_string(&path) {
+                Ok(text) => return Ok((ToolsConfig::parse(&path, &text)?, Some(path))),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(ConfigError::Read(path, e)),
+            }
+        }
+        Ok((ToolsConfig::default(), None))
+    }
+
+    fn validate(&self, path: &Path) -> Result<(), ConfigError> {
+        let invalid = |msg: &str| Err(ConfigError::Invalid(path.to_path_buf(), msg.to_string()));
+        let dirs = [
+            ("paths.audit_dir", &self.paths.audit_dir),
+            ("paths.metrics_dir", &self.paths.metrics_dir),
+            ("audit_dir", &self.audit_dir),
+        ];
+        for (key, dir) in dirs {
+            if dir.as_ref().is_some_and(|d| d.as_os_str().is_empty()) {
+                return invalid(&format!("`{}` must not be empty", key));
+            }
+        }
+        if self.scan.include.iter().any(|g| g.trim().is_empty())
+            || self.scan.exclude.iter().any(|g| g.trim().is_empty())
+        {
+            return invalid("`scan.include` and `scan.exclude` must not contain empty globs");
+        }
+        if self.scan.max_file_size == Some(0) {
+            return invalid("`scan.max_file_size` must be greater than 0");
+        }
+        if self.history.since_days == Some(0) {
+            return invalid("`history.since_days` must be greater than 0");
+        }
+        if self.coupling.min_support == Some(0) {
+            return invalid("`coupling.min_support` must be greater than 0");
+        }
+        if self
+            .coupling
+            .min_degree
+            .is_some_and(|d| !(0.0..=100.0).contains(&d))
+        {
+            return invalid("`coupling.min_degree` must be between 0 and 100");
+        }
+        if self.coupling.max_changeset_size.is_some_and(|n| n < 2) {
+            return invalid("`coupling.max_changeset_size` must be at least 2");
+        }
+        if self.snapshots.keep == Some(0) {
+            return invalid("`snapshots.keep` must be greater than 0");
+        }
+        if self.snapshots.max_age_days == Some(0) {
+            return invalid("`snapshots.max_age_days` must be greater than 0");
+        }
+        Ok(())
+    }
+}
+
+/// The environment layer: `RYOIKI_AUDIT_DIR` (or the older
+/// `TOKADO_AUDIT_DIR`) and `RYOIKI_METRICS_DIR`.
+pub fn env_paths() -> PathsConfig {
+    let var = |name: &str| {
+        std::env::var_os(name)
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+    };
+    PathsConfig {
+        audit_dir: var("RYOIKI_AUDIT_DIR").or_else(|| var("TOKADO_AUDIT_DIR")),
+        metrics_dir: var("RYOIKI_METRICS_DIR"),
+    }
+}
+
+impl ScanConfig {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        ScanConfig {
+            root: root.into(),
+            includes: Vec::new(),
+            excludes: DEFAULT_EXCLUDES.iter().map(|s| s.to_string()).collect(),
+            hidden: false,
+            follow_symlinks: false,
+            max_file_size: None,
+            depth: ScanDepth::Files,
+            outputs: ScanOutputs::default(),
+            cache_path: None,
+            revision: None,
+            history: Some(HistoryOptions {
+                since_days: Some(DEFAULT_HISTORY_DAYS),
+            }),
+            hotspot_top: DEFAULT_HOTSPOT_TOP,
+            coupling: Some(CouplingOptions::default()),
+            snapshots: Some(SnapshotOptions::default()),
+            config_file: None,
+        }
+    }
+
+    /// Loads the project file from `project_root` and layers the environment
+    /// and `cli` on top of it.
+    pub fn from_tools_config(
+        project_root: &Path,
+        cli: &ConfigOverrides,
+    ) -> Result<Self, ConfigError> {
+        let (file, path) = ToolsConfig::load(project_root)?;
+        let mut cfg = ScanConfig::resolve(project_root, &file, &env_paths(), cli);
+        cfg.config_file = path;
+        Ok(cfg)
+    }
+
+    /// Applies the layers without touching the environment or reading files
+    /// (beyond checking whether the bundled web UI exists). Relative audit
+    /// dirs resolve against `project_root`, relative metrics dirs against the
+    /// scan root; absolute paths are used as given.
+    pub fn resolve(
+        project_root: &Path,
+        file: &ToolsConfig,
+        env: &PathsConfig,
+        cli: &ConfigOverrides,
+    ) -> Self {
+        let audit_dir = cli
+            .audit_dir
+            .as_ref()
+            .or(env.audit_dir.as_ref())
+            .or(file.paths.audit_dir.as_ref())
+            .or(file.audit_dir.as_ref())
+            .cloned()
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_AUDIT_DIR));
+        let metrics_dir = cli
+            .metrics_dir
+            .as_ref()
+            .or(env.metrics_dir.as_ref())
+            .or(file.paths.metrics_dir.as_ref())
+            .cloned()
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_METRICS_DIR));
+
+        // `join` keeps absolute paths as they are.
+        let scan_root = project_root.join(audit_dir);
+        let metrics_dir = scan_root.join(metrics_dir);
+
+        let mut cfg = ScanConfig::new(scan_root);
+        cfg.cache_path = Some(metrics_dir.join("ryoiki.cache.json"));
+        cfg.includes = file.scan.include.clone();
+        cfg.excludes.extend(file.scan.exclude.iter().cloned());
+        // Snapshots are JSON; keep them out of the scans they record.
+        if let Ok(rel) = metrics_dir.strip_prefix(&cfg.root)
+            && rel.components().all(|c| matches!(c, Component::Normal(_)))
+        {
+            let rel: Vec<_> = rel.iter().map(|c| c.to_string_lossy()).collect();
+            cfg.excludes
+                .push(format!("/{}/{}", rel.join("/"), SNAPSHOT_DIR).replace("//", "/"));
+        }
+        cfg.hidden = file.scan.hidden.unwrap_or(false);
+        cfg.follow_symlinks = file.scan.follow_symlinks.unwrap_or(false);
+        cfg.max_file_size = file.scan.max_file_size;
+        cfg.depth = file.scan.depth.unwrap_or_default();
+        cfg.history = file
+            .history
+            .enabled
+            .unwrap_or(true)
+            .then_some(HistoryOptions {
+                since_days: Some(file.history.since_days.unwrap_or(DEFAULT_HISTORY_DAYS)),
+            });
+        cfg.hotspot_top = file.hotspots.top.unwrap_or(DEFAULT_HOTSPOT_TOP);
+        let defaults = CouplingOptions::default();
+        let coupling = &file.coupling;
+        cfg.coupling = coupling.enabled.unwrap_or(true).then_some(CouplingOptions {
+            min_support: coupling.min_support.unwrap_or(defaults.min_support),
+            min_degree: coupling.min_degree.unwrap_or(defaults.min_degree),
+            max_changeset_size: coupling
+                .max_changeset_size
+                .unwrap_or(defaults.max_changeset_size),
+        });
+        let snapshots = &file.snapshots;
+        cfg.snapshots = snapshots
+            .enabled
+            .unwrap_or(true)
+            .then_some(SnapshotOptions {
+                keep: Some(snapshots.keep.unwrap_or(DEFAULT_SNAPSHOT_KEEP)),
+                max_age_days: snapshots.max_age_days,
+            });
+        // The bundled web UI only exists when running from a ryoiki checkout.
+        let web_dir = project_root.join("apps/web/public");
+        cfg.outputs = ScanOutputs {
+            metrics_dir: Some(metrics_dir),
+            web_dir: web_dir.is_dir().then_some(web_dir),
+        };
+        cfg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_precedence() {
+        let root = Path::new("proj");
+        let file = ToolsConfig {
+            paths: PathsConfig {
+                audit_dir: Some("from-file".into()),
+                metrics_dir: Some("m".into()),
+            },
+            audit_dir: Some("alias".into()),
+            ..ToolsConfig::default()
+        };
+        let mut env = PathsConfig::default();
+        let mut cli = ConfigOverrides::default();
+
+        let cfg = ScanConfig::resolve(root, &ToolsConfig::default(), &env, &cli);
+        assert_eq!(cfg.root, Path::new("proj/."));
+        assert_eq!(
+            cfg.history,
+            Some(HistoryOptions {
+                since_days: Some(DEFAULT_HISTORY_DAYS)
+            })
+        );
+        assert_eq!(
+            cfg.outputs.metrics_dir.as_deref(),
+            Some(Path::new("proj/./tools/metrics"))
+        );
+
+        let cfg = ScanConfig::resolve(root, &file, &env, &cli);
+        assert_eq!(cfg.root, Path::new("proj/from-file"));
+
+        env.audit_dir = Some("from-env".into());
+        let cfg = ScanConfig::resolve(root, &file, &env, &cli);
+        assert_eq!(cfg.root, Path::new("proj/from-env"));
+
+        cli.audit_dir = Some("/abs/cli".into());
+        let cfg = ScanConfig::resolve(root, &file, &env, &cli);
+        assert_eq!(cfg.root, Path::new("/abs/cli"));
+        assert_eq!(
+            cfg.outputs.metrics_dir.as_deref(),
+            Some(Path::new("/abs/cli/m"))
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_keys_and_reads_toml() {
+        let json = Path::new("tools.config.json");
+        let err = ToolsConfig::parse(json, r#"{"paths": {"audit_dri": "src"}}"#)
+            .expect_err("typo must be rejected");
+        assert!(err.to_string().contains("audit_dri"), "{}", err);
+
+        let toml = Path::new("tools.config.toml");
+        let cfg = ToolsConfig::parse(
+            toml,
+            "[paths]\naudit_dir = \"/srv/repo\"\n\n[scan]\nexclude = [\"vendor\"]\nhidden = true\n",
+        )
+        .expect("valid toml");
+        assert_eq!(cfg.paths.audit_dir.as_deref(), Some(Path::new("/srv/repo")));
+        assert_eq!(cfg.scan.exclude, ["vendor"]);
+        assert_eq!(cfg.scan.hidden, Some(true));
+
+        assert!(ToolsConfig::parse(toml, "[scan]\nmax_file_size = 0\n").is_err());
+        assert!(ToolsConfig::parse(toml, "[scan]\ndepth = \"lines\"\n").is_err());
+        let cfg = ToolsConfig::parse(json, r#"{"scan": {"depth": "functions"}}"#).expect("valid");
+        let resolved = ScanConfig::resolve(
+            Path::new("proj"),
+            &cfg,
+            &PathsConfig::default(),
+            &ConfigOverrides::default(),
+        );
+        assert_eq!(resolved.depth, ScanDepth::Functions);
+    }
+}
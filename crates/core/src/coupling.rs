@@ -0,0 +1,122 @@
+//! Temporal coupling: files that change in the same commits.
+//!
+//! For every pair of files the commits touching both are counted (the
+//! support). Pairs that co-change rarely, or only as a small share of their
+//! own history, are dropped, as are commits touching so many files that
+//! every pair in them would look coupled (mass renames, formatting runs).
+
+use crate::config::CouplingOptions;
+use crate::history::History;
+use crate::report::CouplingEdge;
+use std::collections::HashMap;
+
+/// Coupling edges between the files of `paths`, which maps the
+/// `/`-separated paths [`History`] uses to the [`crate::Node::path`] the
+/// edge should name. Files missing from it (deleted, excluded) are ignored.
+///
+/// Edges are sorted by degree, strongest first.
+pub fn coupling_edges(
+    history: &History,
+    paths: &HashMap<String, String>,
+    opts: &CouplingOptions,
+) -> Vec<CouplingEdge> {
+    let mut commits: HashMap<&str, usize> = HashMap::new();
+    let mut shared: HashMap<(&str, &str), usize> = HashMap::new();
+    for commit in &history.commits {
+        let files: Vec<&str> = commit
+            .files
+            .iter()
+            .filter_map(|f| paths.get(&f.path).map(|p| p.as_str()))
+            .collect();
+        if files.len() > opts.max_changeset_size {
+            continue;
+        }
+        for (i, a) in files.iter().enumerate() {
+            *commits.entry(a).or_default() += 1;
+            for b in &files[i + 1..] {
+                let key = if a <= b { (*a, *b) } else { (*b, *a) };
+                *shared.entry(key).or_default() += 1;
+            }
+        }
+    }
+
+    let mut edges: Vec<CouplingEdge> = shared
+        .into_iter()
+        .filter(|(_, support)| *support >= opts.min_support)
+        .filter_map(|((from, to), support)| {
+            let (ca, cb) = (commits[from], commits[to]);
+            let degree = support as f64 * 100.0 / ((ca + cb) as f64 / 2.0);
+            (degree >= opts.min_degree).then(|| CouplingEdge {
+                from: from.to_string(),
+                to: to.to_string(),
+                support,
+                confidence: support as f64 / ca.min(cb) as f64,
+                degree,
+            })
+        })
+        .collect();
+    edges.sort_by(|a, b| {
+        b.degree
+            .total_cmp(&a.degree)
+            .then_with(|| b.support.cmp(&a.support))
+            .then_with(|| a.from.cmp(&b.from))
+            .then_with(|| a.to.cmp(&b.to))
+    });
+    edges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::{CommitFile, HistoryCommit};
+
+    fn commit(files: &[&str]) -> HistoryCommit {
+        HistoryCommit {
+            id: String::new(),
+            time: 0,
+            author: String::new(),
+            files: files
+                .iter()
+                .map(|p| CommitFile {
+                    path: p.to_string(),
+                    added: 1,
+                    deleted: 0,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_coupling_edges_apply_thresholds() {
+        let history = History {
+            head_time: 0,
+            commits: vec![
+                commit(&["a", "b"]),
+                commit(&["a", "b", "c"]),
+                commit(&["a", "b", "gone"]),
+                commit(&["a", "c"]),
+                commit(&["a", "b", "c", "d"]),
+                commit(&["d"]),
+            ],
+        };
+        let paths: HashMap<String, String> = ["a", "b", "c", "d"]
+            .iter()
+            .map(|p| (p.to_string(), p.to_string()))
+            .collect();
+        let opts = CouplingOptions {
+            min_support: 2,
+            min_degree: 50.0,
+            max_changeset_size: 3,
+        };
+        let edges = coupling_edges(&history, &paths, &opts);
+
+        // The four-file commit is skipped; `gone` is not in the tree.
+        let pairs: Vec<(&str, &str, usize)> = edges
+            .iter()
+            .map(|e| (e.from.as_str(), e.to.as_str(), e.support))
+            .collect();
+        assert_eq!(pairs, [("a", "b", 3), ("a", "c", 2)]);
+        assert!((edges[0].degree - 3.0 * 100.0 / 3.5).abs() < 1e-9);
+        assert!((edges[0].confidence - 1.0).abs() < 1e-9);
+    }
+}
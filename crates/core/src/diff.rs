@@ -0,0 +1,450 @@
+use crate::report::{Metrics, Node};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Signed difference between two [`Metrics`] values (`after - before`).
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct MetricsDelta {
+    pub loc: i64,
+    pub complexity: i64,
+    pub functions: i64,
+    #[serde(default)]
+    pub code: i64,
+    #[serde(default)]
+    pub lines: i64,
+    #[serde(default)]
+    pub comments: i64,
+    #[serde(default)]
+    pub blanks: i64,
+    #[serde(default)]
+    pub cognitive: i64,
+    #[serde(default)]
+    pub max_nesting: i64,
+}
+
+impl MetricsDelta {
+    pub fn between(before: &Metrics, after: &Metrics) -> Self {
+        MetricsDelta {
+            loc: after.loc as i64 - before.loc as i64,
+            complexity: after.complexity as i64 - before.complexity as i64,
+            functions: after.functions as i64 - before.functions as i64,
+            code: after.code as i64 - before.code as i64,
+            lines: after.lines as i64 - before.lines as i64,
+            comments: after.comments as i64 - before.comments as i64,
+            blanks: after.blanks as i64 - before.blanks as i64,
+            cognitive: after.cognitive as i64 - before.cognitive as i64,
+            max_nesting: after.max_nesting as i64 - before.max_nesting as i64,
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        *self == MetricsDelta::default()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FileChange {
+    pub path: String,
+    pub before: Metrics,
+    pub after: Metrics,
+    pub delta: MetricsDelta,
+}
+
+/// File-level comparison of two scan trees.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TreeDiff {
+    pub before: Metrics,
+    pub after: Metrics,
+    pub delta: MetricsDelta,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<FileChange>,
+}
+
+impl TreeDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+pub fn diff_trees(before: &Node, after: &Node) -> TreeDiff {
+    let mut a = BTreeMap::new();
+    let mut b = BTreeMap::new();
+    collect_files(before, &mut a);
+    collect_files(after, &mut b);
+
+    let mut added = Vec::new();
+    let mut removed = Vec::new();
+    let mut changed = Vec::new();
+    for (path, m_before) in &a {
+        match b.get(path) {
+            Some(m_after) => {
+                let delta = MetricsDelta::between(m_before, m_after);
+                if !delta.is_zero() {
+                    changed.push(FileChange {
+                        path: path.to_string(),
+                        before: (*m_before).clone(),
+                        after: (*m_after).clone(),
+                        delta,
+                    });
+                }
+            }
+            None => removed.push(path.to_string()),
+        }
+    }
+    for path in b.keys() {
+        if !a.contains_key(path) {
+            added.push(path.to_string());
+        }
+    }
+
+    TreeDiff {
+        before: before.metrics.clone(),
+        after: after.metrics.clone(),
+        delta: MetricsDelta::between(&before.metrics, &after.metrics),
+        added,
+        removed,
+        changed,
+    }
+}
+
+fn collect_files<'a>(node: &'a Node, out: &mut BTreeMap<&'a str, &'a Metrics>) {
+    match node.entries() {
+        Some(children) => {
+            for c in children {
+                collect_files(c, out);
+            }
+        }
+        None => {
+            out.insert(node.path.as_str(), &node.metrics);
+        }
+    }
+}
+
+/// How a [`DeltaNode`] differs between the two trees.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DeltaStatus {
+    Unchanged,
+    /// Present in both trees with different metrics; for a directory, some
+    /// file below it changed.
+    Changed,
+    Added,
+    Removed,
+    /// A file that disappeared from one path and reappeared, with identical
+    /// metrics, at another.
+    Moved,
+}
+
+/// An imported attribute (see [`Node::attributes`]) in both trees. A side
+/// that lacks the attribute counts as zero.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AttributeChange {
+    pub before: f64,
+    pub after: f64,
+    pub delta: f64,
+}
+
+/// One node of the tree [`delta_tree`] builds: the union of both trees, with
+/// every node carrying its metrics on each side. The missing side of an added
+/// or removed node is all zeros, and directories add up their children.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DeltaNode {
+    pub name: String,
+    /// The path in the second tree, or in the first for removed nodes.
+    pub path: String,
+    pub kind: String,
+    pub language: Option<String>,
+    pub status: DeltaStatus,
+    /// The path in the first tree of a [`DeltaStatus::Moved`] file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub moved_from: Option<String>,
+    pub before: Metrics,
+    pub after: Metrics,
+    pub delta: MetricsDelta,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub attributes: BTreeMap<String, AttributeChange>,
+    pub children: Option<Vec<DeltaNode>>,
+}
+
+/// Builds the delta tree of two scans, CodeCharta's delta mode.
+///
+/// Nodes are matched by path. A file removed from one path and added at
+/// another counts as moved when it is the only removed and the only added
+/// non-empty file with that name, language and set of metrics; the moved
+/// file appears once, at its new path.
+pub fn delta_tree(before: &Node, after: &Node) -> DeltaNode {
+    let mut old = BTreeMap::new();
+    let mut new = BTreeMap::new();
+    collect_file_nodes(before, &mut old);
+    collect_file_nodes(after, &mut new);
+    let moves = detect_moves(&old, &new);
+    let ctx = DeltaContext {
+        sources: moves.values().map(|n| n.path.as_str()).collect(),
+        moves,
+    };
+    build_delta(Some(before), Some(after), &ctx).unwrap_or_else(|| DeltaNode {
+        name: after.name.clone(),
+        path: after.path.clone(),
+        kind: after.kind.clone(),
+        language: after.language.clone(),
+        status: DeltaStatus::Unchanged,
+        moved_from: None,
+        before: Metrics::default(),
+        after: Metrics::default(),
+        delta: MetricsDelta::default(),
+        attributes: BTreeMap::new(),
+        children: Some(Vec::new()),
+    })
+}
+
+struct DeltaContext<'a> {
+    /// New path to the file it was moved from.
+    moves: HashMap<&'a str, &'a Node>,
+    /// Old paths of moved files.
+    sources: HashSet<&'a str>,
+}
+
+fn children_of(node: Option<&Node>) -> &[Node] {
+    node.and_then(Node::entries).unwrap_or(&[])
+}
+
+fn collect_file_nodes<'a>(node: &'a Node, out: &mut BTreeMap<&'a str, &'a Node>) {
+    match node.entries() {
+        Some(children) => children.iter().for_each(|c| collect_file_nodes(c, out)),
+        None => {
+            out.insert(node.path.as_str(), node);
+        }
+    }
+}
+
+fn detect_moves<'a>(
+    old: &BTreeMap<&'a str, &'a Node>,
+    new: &BTreeMap<&'a str, &'a Node>,
+) -> HashMap<&'a str, &'a Node> {
+    type Key<'k> = (&'k str, Option<&'k str>, [usize; 6]);
+    let key = |n: &'a Node| -> Key<'a> {
+        let m = &n.metrics;
+        (
+            n.name.as_str(),
+            n.language.as_deref(),
+            [
+                m.loc,
+                m.complexity,
+                m.functions,
+                m.code,
+                m.comments,
+                m.blanks,
+            ],
+        )
+    };
+    let mut candidates: HashMap<Key<'a>, (Vec<&'a Node>, Vec<&'a Node>)> = HashMap::new();
+    for (path, n) in old {
+        if !new.contains_key(path) && n.metrics.loc > 0 {
+            candidates.entry(key(n)).or_default().0.push(n);
+        }
+    }
+    for (path, n) in new {
+        if !old.contains_key(path) && n.metrics.loc > 0 {
+            candidates.entry(key(n)).or_default().1.push(n);
+        }
+    }
+    candidates
+        .into_values()
+        .filter_map(
+            |(removed, added)| match (removed.as_slice(), added.as_slice()) {
+                (&[from], &[to]) => Some((to.path.as_str(), from)),
+                _ => None,
+            },
+        )
+        .collect()
+}
+
+/// Both sides of the children of a node, keyed by name and whether they are
+/// directories.
+type Sides<'a> = BTreeMap<(&'a str, bool), (Option<&'a Node>, Option<&'a Node>)>;
+
+/// The delta of `before` and `after`, which share a path; `None` for a
+/// removed node that only held moved files.
+fn build_delta(
+    before: Option<&Node>,
+    after: Option<&Node>,
+    ctx: &DeltaContext,
+) -> Option<DeltaNode> {
+    let node = after.or(before)?;
+    let (before, moved_from) = match (before, after) {
+        (None, Some(a)) => match ctx.moves.get(a.path.as_str()) {
+            Some(from) => (Some(*from), Some(from.path.clone())),
+            None => (None, None),
+        },
+        (Some(b), None) if ctx.sources.contains(b.path.as_str()) => return None,
+        _ => (before, None),
+    };
+
+    let children = match node.entries() {
+        Some(_) => {
+            let mut by_name: Sides = BTreeMap::new();
+            for c in children_of(before) {
+                by_name
+                    .entry((&c.name, c.entries().is_some()))
+                    .or_default()
+                    .0 = Some(c);
+            }
+            for c in children_of(after) {
+                by_name
+                    .entry((&c.name, c.entries().is_some()))
+                    .or_default()
+                    .1 = Some(c);
+            }
+            let children: Vec<DeltaNode> = by_name
+                .into_values()
+                .filter_map(|(b, a)| build_delta(b, a, ctx))
+                .collect();
+            if after.is_none() && children.is_empty() && !children_of(before).is_empty() {
+                return None;
+            }
+            Some(children)
+        }
+        None => None,
+    };
+
+    let (before_metrics, after_metrics) = match &children {
+        Some(children) => {
+            let mut sums = (Metrics::default(), Metrics::default());
+            for c in children {
+                sums.0 += &c.before;
+                sums.1 += &c.after;
+            }
+            sums
+        }
+        None => (
+            before.map(|n| n.metrics.clone()).unwrap_or_default(),
+            after.map(|n| n.metrics.clone()).unwrap_or_default(),
+        ),
+    };
+    let delta = MetricsDelta::between(&before_metrics, &after_metrics);
+
+    let mut attributes: BTreeMap<String, AttributeChange> = BTreeMap::new();
+    for (side, n) in [(0, before), (1, after)] {
+        for (name, value) in n.iter().flat_map(|n| &n.attributes) {
+            let e = attributes.entry(name.clone()).or_insert(AttributeChange {
+                before: 0.0,
+                after: 0.0,
+                delta: 0.0,
+            });
+            if side == 0 {
+                e.before = *value;
+            } else {
+                e.after = *value;
+            }
+        }
+    }
+    for a in attributes.values_mut() {
+        a.delta = a.after - a.before;
+    }
+
+    let status = match (before, after) {
+        (_, Some(_)) if moved_from.is_some() => DeltaStatus::Moved,
+        (None, _) => DeltaStatus::Added,
+        (_, None) => DeltaStatus::Removed,
+        _ => {
+            let changed = !delta.is_zero()
+                || attributes.values().any(|a| a.delta != 0.0)
+                || children
+                    .iter()
+                    .flatten()
+                    .any(|c| c.status != DeltaStatus::Unchanged);
+            if changed {
+                DeltaStatus::Changed
+            } else {
+                DeltaStatus::Unchanged
+            }
+        }
+    };
+
+    Some(DeltaNode {
+        name: node.name.clone(),
+        path: node.path.clone(),
+        kind: node.kind.clone(),
+        language: node.language.clone(),
+        status,
+        moved_from,
+        before: before_metrics,
+        after: after_metrics,
+        delta,
+        attributes,
+        children,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str, loc: usize) -> Node {
+        Node {
+            language: Some("rust".to_string()),
+            ..Node::file(
+                path,
+                Metrics {
+                    loc,
+                    code: loc,
+                    ..Metrics::default()
+                },
+            )
+        }
+    }
+
+    fn dir(path: &str, children: Vec<Node>) -> Node {
+        Node::directory(path, children)
+    }
+
+    #[test]
+    fn test_delta_tree_marks_added_removed_and_moved_files() {
+        let before = dir(
+            ".",
+            vec![
+                dir("old", vec![file("old/mv.rs", 7)]),
+                file("gone.rs", 3),
+                file("same.rs", 2),
+                file("grow.rs", 4),
+            ],
+        );
+        let after = dir(
+            ".",
+            vec![
+                dir("new", vec![file("new/mv.rs", 7), file("new/fresh.rs", 5)]),
+                file("same.rs", 2),
+                file("grow.rs", 10),
+            ],
+        );
+        let delta = delta_tree(&before, &after);
+
+        let status: Vec<(&str, DeltaStatus)> = delta
+            .children
+            .as_ref()
+            .expect("children")
+            .iter()
+            .map(|c| (c.path.as_str(), c.status))
+            .collect();
+        assert_eq!(
+            status,
+            [
+                ("gone.rs", DeltaStatus::Removed),
+                ("grow.rs", DeltaStatus::Changed),
+                ("new", DeltaStatus::Added),
+                ("same.rs", DeltaStatus::Unchanged),
+            ]
+        );
+        let new = &delta.children.as_ref().expect("children")[2];
+        let moved = &new.children.as_ref().expect("children")[1];
+        assert_eq!(moved.status, DeltaStatus::Moved);
+        assert_eq!(moved.moved_from.as_deref(), Some("old/mv.rs"));
+        assert_eq!((moved.before.loc, moved.delta.loc), (7, 0));
+        assert_eq!((new.before.loc, new.after.loc, new.delta.loc), (7, 12, 5));
+
+        assert_eq!(delta.status, DeltaStatus::Changed);
+        assert_eq!((delta.before.loc, delta.after.loc), (16, 24));
+        assert_eq!(delta.delta.loc, 8);
+    }
+}
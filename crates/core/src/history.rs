@@ -0,0 +1,351 @@
+//! Change history mined from the git repository around the scan root.
+//!
+//! [`read_history`] walks the commits reachable from `HEAD` (or the scanned
+//! revision) with gix and
+//! records, per commit, which files under the scan root changed and by how
+//! many lines. [`annotate`] turns that into [`GitMetrics`] on every node.
+
+use crate::config::HistoryOptions;
+use crate::report::{GitMetrics, Node, ScanCommit};
+use gix::revision::walk::Sorting;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// The commits in the history window, newest first. Merge commits are
+/// skipped, as `git log --numstat` does by default.
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    /// Commit time of `HEAD`, or of the scanned revision, in seconds since
+    /// the epoch. Ages are measured from here so that rescans of the same
+    /// commit agree.
+    pub head_time: i64,
+    pub commits: Vec<HistoryCommit>,
+}
+
+#[derive(Debug, Clone)]
+pub struct HistoryCommit {
+    pub id: String,
+    /// Commit time in seconds since the epoch.
+    pub time: i64,
+    /// Lowercased author email.
+    pub author: String,
+    /// Files under the scan root the commit touched, sorted by path.
+    pub files: Vec<CommitFile>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CommitFile {
+    /// Relative to the scan root, `/`-separated.
+    pub path: String,
+    /// Zero for binary files.
+    pub added: usize,
+    pub deleted: usize,
+}
+
+#[derive(Debug)]
+pub enum HistoryError {
+    Git(PathBuf, String),
+}
+
+impl fmt::Display for HistoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HistoryError::Git(p, e) => {
+                write!(f, "cannot read git history of {}: {}", p.display(), e)
+            }
+        }
+    }
+}
+
+impl std::error::Error for HistoryError {}
+
+/// Reads the history of the repository containing `root`, starting at
+/// `revision` or, when `None`, at `HEAD`. Returns `Ok(None)` when `root` is
+/// not inside a git work tree or `HEAD` has no commits yet; a `revision`
+/// that does not resolve to a commit is an error.
+///
+/// Renames are not followed: a moved file starts a fresh history.
+pub fn read_history(
+    root: &Path,
+    revision: Option<&str>,
+    opts: &HistoryOptions,
+) -> Result<Option<History>, HistoryError> {
+    let git = |e: &dyn fmt::Display| HistoryError::Git(root.to_path_buf(), e.to_string());
+    let Ok(repo) = gix::discover(root) else {
+        return Ok(None);
+    };
+    let head = match revision {
+        Some(rev) => resolve_commit(&repo, rev).map_err(|e| git(&e))?,
+        None => match repo.head_commit() {
+            Ok(head) => head,
+            Err(_) => return Ok(None),
+        },
+    };
+    let prefix = match workdir_prefix(&repo, root) {
+        Ok(Some(prefix)) => prefix,
+        Ok(None) => return Ok(None),
+        Err(e) => return Err(git(&e)),
+    };
+
+    let head_time = head.time().map_err(|e| git(&e))?.seconds;
+    let order = Default::default();
+    let sorting = match opts.since_days {
+        Some(days) => Sorting::ByCommitTimeCutoff {
+            order,
+            seconds: head_time.saturating_sub(days as i64 * SECONDS_PER_DAY),
+        },
+        None => Sorting::ByCommitTime(order),
+    };
+    let walk = repo
+        .rev_walk([head.id])
+        .sorting(sorting)
+        .all()
+        .map_err(|e| git(&e))?;
+
+    let mut tree_cache = repo
+        .diff_resource_cache_for_tree_diff()
+        .map_err(|e| git(&e))?;
+    let mut line_cache = repo
+        .diff_resource_cache_for_tree_diff()
+        .map_err(|e| git(&e))?;
+    let mut commits = Vec::new();
+    for info in walk {
+        let info = info.map_err(|e| git(&e))?;
+        if info.parent_ids.len() > 1 {
+            continue;
+        }
+        let commit = info.object().map_err(|e| git(&e))?;
+        let tree = commit.tree().map_err(|e| git(&e))?;
+        let parent_tree = match info.parent_ids.first() {
+            Some(id) => repo
+                .find_commit(*id)
+                .map_err(|e| git(&e))?
+                .tree()
+                .map_err(|e| git(&e))?,
+            None => repo.empty_tree(),
+        };
+
+        let mut files = Vec::new();
+        parent_tree
+            .changes()
+            .map_err(|e| git(&e))?
+            .options(|o| {
+                o.track_path().track_rewrites(None);
+            })
+            .for_each_to_obtain_tree_with_cache(&tree, &mut tree_cache, |change| {
+                if !change.entry_mode().is_blob_or_symlink() {
+                    return Ok::<_, std::convert::Infallible>(
+                        gix::object::tree::diff::Action::Continue,
+                    );
+                }
+                let location = change.location().to_string();
+                if let Some(path) = location.strip_prefix(prefix.as_str()) {
+                    let counts = change
+                        .diff(&mut line_cache)
+                        .ok()
+                        .and_then(|mut p| p.line_counts().ok())
+                        .flatten();
+                    files.push(CommitFile {
+                        path: path.to_string(),
+                        added: counts.as_ref().map_or(0, |c| c.insertions as usize),
+                        deleted: counts.as_ref().map_or(0, |c| c.removals as usize),
+                    });
+                }
+                line_cache.clear_resource_cache_keep_allocation();
+                Ok(gix::object::tree::diff::Action::Continue)
+            })
+            .map_err(|e| git(&e))?;
+        tree_cache.clear_resource_cache_keep_allocation();
+        if files.is_empty() {
+            continue;
+        }
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let author = commit.author().map_err(|e| git(&e))?;
+        commits.push(HistoryCommit {
+            id: info.id.to_string(),
+            time: commit.time().map_err(|e| git(&e))?.seconds,
+            author: author.email.to_string().to_lowercase(),
+            files,
+        });
+    }
+    Ok(Some(History { head_time, commits }))
+}
+
+/// `HEAD` of the repository containing `root`, if there is one.
+pub(crate) fn head_commit(root: &Path) -> Option<ScanCommit> {
+    let repo = gix::discover(root).ok()?;
+    scan_commit(&repo.head_commit().ok()?).ok()
+}
+
+pub(crate) fn scan_commit(commit: &gix::Commit<'_>) -> Result<ScanCommit, String> {
+    Ok(ScanCommit {
+        id: commit.id.to_string(),
+        time: commit.time().map_err(|e| e.to_string())?.seconds,
+    })
+}
+
+/// Peels `rev` (a commit id, tag or branch name) to a commit.
+pub(crate) fn resolve_commit<'repo>(
+    repo: &'repo gix::Repository,
+    rev: &str,
+) -> Result<gix::Commit<'repo>, String> {
+    repo.rev_parse_single(rev)
+        .map_err(|e| e.to_string())?
+        .object()
+        .map_err(|e| e.to_string())?
+        .peel_to_commit()
+        .map_err(|e| e.to_string())
+}
+
+/// The path of `root` inside the work tree of `repo`, `/`-separated with a
+/// trailing `/` (empty for the work tree itself). `None` for bare
+/// repositories and roots outside the work tree.
+pub(crate) fn workdir_prefix(repo: &gix::Repository, root: &Path) -> io::Result<Option<String>> {
+    let Some(workdir) = repo.workdir() else {
+        return Ok(None);
+    };
+    let (root, workdir) = (root.canonicalize()?, workdir.canonicalize()?);
+    Ok(root.strip_prefix(&workdir).ok().map(|rel| {
+        rel.components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string() + "/")
+            .collect()
+    }))
+}
+
+/// Sets [`Node::git`] on `tree` and all of its descendants.
+pub(crate) fn annotate(tree: &mut Node, history: &History) {
+    let mut by_path: HashMap<&str, Vec<(usize, &CommitFile)>> = HashMap::new();
+    for (i, c) in history.commits.iter().enumerate() {
+        for f in &c.files {
+            by_path.entry(f.path.as_str()).or_default().push((i, f));
+        }
+    }
+    annotate_node(tree, history, &by_path);
+}
+
+#[derive(Default)]
+struct Activity {
+    commits: BTreeSet<usize>,
+    added: usize,
+    deleted: usize,
+}
+
+fn annotate_node(
+    node: &mut Node,
+    history: &History,
+    by_path: &HashMap<&str, Vec<(usize, &CommitFile)>>,
+) -> Activity {
+    let mut activity = Activity::default();
+    match node.entries_mut() {
+        Some(children) => {
+            for child in children {
+                let a = annotate_node(child, history, by_path);
+                activity.commits.extend(a.commits);
+                activity.added += a.added;
+                activity.deleted += a.deleted;
+            }
+        }
+        None => {
+            let path = node.path.replace('\\', "/");
+            for (i, f) in by_path.get(path.as_str()).into_iter().flatten() {
+                activity.commits.insert(*i);
+                activity.added += f.added;
+                activity.deleted += f.deleted;
+            }
+        }
+    }
+
+    let age = |i: &usize| {
+        let secs = history.head_time.saturating_sub(history.commits[*i].time);
+        (secs.max(0) / SECONDS_PER_DAY) as u64
+    };
+    let authors: BTreeSet<&str> = activity
+        .commits
+        .iter()
+        .map(|i| history.commits[*i].author.as_str())
+        .collect();
+    node.git = Some(GitMetrics {
+        commits: activity.commits.len(),
+        lines_added: activity.added,
+        lines_deleted: activity.deleted,
+        authors: authors.len(),
+        first_commit: activity.commits.iter().map(age).max(),
+        last_commit: activity.commits.iter().map(age).min(),
+    });
+    activity
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::Metrics;
+
+    fn node(path: &str, children: Option<Vec<Node>>) -> Node {
+        match children {
+            Some(children) => Node::directory(path, children),
+            None => Node::file(path, Metrics::default()),
+        }
+    }
+
+    fn commit(days_ago: i64, author: &str, files: &[(&str, usize, usize)]) -> HistoryCommit {
+        HistoryCommit {
+            id: String::new(),
+            time: 100 * SECONDS_PER_DAY - days_ago * SECONDS_PER_DAY,
+            author: author.to_string(),
+            files: files
+                .iter()
+                .map(|(path, added, deleted)| CommitFile {
+                    path: path.to_string(),
+                    added: *added,
+                    deleted: *deleted,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_annotate_counts_directory_commits_once() {
+        let history = History {
+            head_time: 100 * SECONDS_PER_DAY,
+            commits: vec![
+                commit(1, "b@x", &[("src/a.rs", 2, 1), ("src/b.rs", 3, 0)]),
+                commit(10, "a@x", &[("src/a.rs", 5, 0)]),
+            ],
+        };
+        let mut tree = node(
+            ".",
+            Some(vec![
+                node("README.md", None),
+                node(
+                    "src",
+                    Some(vec![node("src/a.rs", None), node("src/b.rs", None)]),
+                ),
+            ]),
+        );
+        annotate(&mut tree, &history);
+
+        let children = tree.children.as_ref().expect("children");
+        assert_eq!(children[0].git.as_ref().map(|g| g.commits), Some(0));
+        assert_eq!(children[0].git.as_ref().and_then(|g| g.last_commit), None);
+        let a = &children[1].children.as_ref().expect("children")[0];
+        assert_eq!(
+            a.git,
+            Some(GitMetrics {
+                commits: 2,
+                lines_added: 7,
+                lines_deleted: 1,
+                authors: 2,
+                first_commit: Some(10),
+                last_commit: Some(1),
+            })
+        );
+        let root = tree.git.as_ref().expect("root history");
+        assert_eq!((root.commits, root.authors), (2, 2));
+        assert_eq!((root.lines_added, root.lines_deleted), (10, 1));
+    }
+}
@@ -0,0 +1,133 @@
+//! Hotspots: files that are both complex and frequently changed.
+//!
+//! A file's score is its complexity divided by the highest file complexity,
+//! times its commit count divided by the highest commit count. Both factors
+//! are in `0..=1`, so the score is too, and a file only scores high when it
+//! is near the top on both.
+
+use crate::report::{Hotspot, Node};
+
+/// Sets [`Node::hotspot_score`] on every node of a tree that carries git
+/// history and returns the `top` highest-scoring files, best first. Files
+/// that scored zero are not listed. Trees without history are left alone.
+pub fn score_hotspots(tree: &mut Node, top: usize) -> Vec<Hotspot> {
+    let mut max_complexity = 0;
+    let mut max_commits = 0;
+    visit_files(tree, &mut |n| {
+        if let Some(git) = &n.git {
+            max_complexity = max_complexity.max(n.metrics.complexity);
+            max_commits = max_commits.max(git.commits);
+        }
+    });
+    if tree.git.is_none() {
+        return Vec::new();
+    }
+
+    let mut ranked = Vec::new();
+    assign(tree, max_complexity, max_commits, &mut ranked);
+    ranked.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    ranked.truncate(top);
+    ranked
+}
+
+fn visit_files(node: &Node, f: &mut impl FnMut(&Node)) {
+    match node.entries() {
+        Some(children) => children.iter().for_each(|c| visit_files(c, f)),
+        None => f(node),
+    }
+}
+
+fn assign(
+    node: &mut Node,
+    max_complexity: usize,
+    max_commits: usize,
+    ranked: &mut Vec<Hotspot>,
+) -> f64 {
+    let score = match node.entries_mut() {
+        Some(children) => children
+            .iter_mut()
+            .map(|c| assign(c, max_complexity, max_commits, ranked))
+            .fold(0.0, f64::max),
+        None => {
+            let commits = node.git.as_ref().map_or(0, |g| g.commits);
+            let score = if max_complexity == 0 || max_commits == 0 {
+                0.0
+            } else {
+                (node.metrics.complexity as f64 / max_complexity as f64)
+                    * (commits as f64 / max_commits as f64)
+            };
+            if score > 0.0 {
+                ranked.push(Hotspot {
+                    path: node.path.clone(),
+                    score,
+                    complexity: node.metrics.complexity,
+                    commits,
+                });
+            }
+            score
+        }
+    };
+    node.hotspot_score = Some(score);
+    score
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::{GitMetrics, Metrics};
+
+    fn node(path: &str, complexity: usize, commits: usize, children: Option<Vec<Node>>) -> Node {
+        let metrics = Metrics {
+            complexity,
+            ..Metrics::default()
+        };
+        let node = match children {
+            Some(children) => Node {
+                metrics,
+                ..Node::directory(path, children)
+            },
+            None => Node::file(path, metrics),
+        };
+        Node {
+            name: path.to_string(),
+            git: Some(GitMetrics {
+                commits,
+                ..GitMetrics::default()
+            }),
+            ..node
+        }
+    }
+
+    #[test]
+    fn test_score_hotspots_ranks_complex_and_busy_files() {
+        let mut tree = node(
+            ".",
+            0,
+            0,
+            Some(vec![
+                node("busy.rs", 2, 10, None),
+                node("complex.rs", 8, 1, None),
+                node("both.rs", 6, 8, None),
+                node("quiet.rs", 4, 0, None),
+            ]),
+        );
+        let top = score_hotspots(&mut tree, 2);
+
+        let paths: Vec<&str> = top.iter().map(|h| h.path.as_str()).collect();
+        assert_eq!(paths, ["both.rs", "busy.rs"]);
+        assert!((top[0].score - 0.75 * 0.8).abs() < 1e-9);
+        assert_eq!(tree.hotspot_score, Some(top[0].score));
+        let quiet = &tree.children.as_ref().expect("children")[3];
+        assert_eq!(quiet.hotspot_score, Some(0.0));
+
+        let mut plain = tree.clone();
+        plain.git = None;
+        plain.hotspot_score = None;
+        assert!(score_hotspots(&mut plain, 2).is_empty());
+        assert_eq!(plain.hotspot_score, None);
+    }
+}
@@ -0,0 +1,224 @@
+//! Loading trees produced by ryoiki or by CodeCharta tooling (`ccsh`,
+//! SonarQube importers, ...).
+
+use crate::codecharta::{CcProject, from_codecharta};
+use crate::language::dominant_language;
+use crate::report::{Maintainability, Metrics, Node};
+use flate2::read::GzDecoder;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Read;
+
+#[derive(Debug)]
+pub enum ImportError {
+    Gzip(std::io::Error),
+    Utf8(std::string::FromUtf8Error),
+    Json(serde_json::Error),
+    EmptyProject,
+    /// The gzip input expands beyond [`MAX_DECOMPRESSED`] bytes.
+    TooLarge,
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::Gzip(e) => write!(f, "gzip decode failed: {}", e),
+            ImportError::Utf8(e) => write!(f, "input is not UTF-8: {}", e),
+            ImportError::Json(e) => write!(f, "JSON parse failed: {}", e),
+            ImportError::EmptyProject => write!(f, "CodeCharta project has no nodes"),
+            ImportError::TooLarge => write!(
+                f,
+                "gzip input expands beyond {} MB",
+                MAX_DECOMPRESSED / (1024 * 1024)
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+/// The most a gzip-compressed tree may expand to, so a small upload cannot
+/// exhaust memory.
+pub const MAX_DECOMPRESSED: u64 = 1024 * 1024 * 1024;
+
+/// Parses a `ryoiki.cc.json` tree or a CodeCharta `cc.json` file, plain or
+/// gzip-compressed (`.cc.json.gz`). CodeCharta's `{checksum, data}` wrapper
+/// is unwrapped as well.
+pub fn parse_tree(bytes: &[u8]) -> Result<Node, ImportError> {
+    let text = if bytes.starts_with(&[0x1f, 0x8b]) {
+        String::from_utf8(gunzip(bytes, MAX_DECOMPRESSED)?).map_err(ImportError::Utf8)?
+    } else {
+        String::from_utf8(bytes.to_vec()).map_err(ImportError::Utf8)?
+    };
+    let mut v: serde_json::Value = serde_json::from_str(&text).map_err(ImportError::Json)?;
+    if v.get("checksum").is_some()
+        && let Some(data) = v.get_mut("data")
+    {
+        v = data.take();
+    }
+    if v.get("nodes").is_some() {
+        let project: CcProject = serde_json::from_value(v).map_err(ImportError::Json)?;
+        return from_codecharta(&project).ok_or(ImportError::EmptyProject);
+    }
+    serde_json::from_value(v).map_err(ImportError::Json)
+}
+
+/// Decompresses `bytes`, failing once the output exceeds `limit` bytes.
+fn gunzip(bytes: &[u8], limit: u64) -> Result<Vec<u8>, ImportError> {
+    let mut out = Vec::new();
+    GzDecoder::new(bytes)
+        .take(limit + 1)
+        .read_to_end(&mut out)
+        .map_err(ImportError::Gzip)?;
+    if out.len() as u64 > limit {
+        return Err(ImportError::TooLarge);
+    }
+    Ok(out)
+}
+
+/// Merges trees by path, the way `ccsh merge` does: folders are combined,
+/// files present in several inputs get the union of their attributes (later
+/// inputs win) and directory totals are recomputed. The result keeps the
+/// first tree's name.
+pub fn merge_trees(trees: Vec<Node>) -> Option<Node> {
+    let mut iter = trees.into_iter();
+    let mut merged = iter.next()?;
+    for t in iter {
+        merge_into(&mut merged, t);
+    }
+    merged.path = ".".to_string();
+    refresh_directory(&mut merged);
+    Some(merged)
+}
+
+fn merge_into(dst: &mut Node, src: Node) {
+    let src_is_dir = src.entries().is_some();
+    dst.attributes.extend(src.attributes);
+    match (dst.entries_mut(), src_is_dir) {
+        (Some(dst_children), true) => {
+            for s in src.children.into_iter().flatten() {
+                match dst_children.iter_mut().find(|d| d.name == s.name) {
+                    Some(d) => merge_into(d, s),
+                    None => dst_children.push(s),
+                }
+            }
+        }
+        (None, false) => {
+            if dst.children.is_none() {
+                dst.children = src.children;
+            }
+            let m = &mut dst.metrics;
+            let s = &src.metrics;
+            for (d, v) in [
+                (&mut m.loc, s.loc),
+                (&mut m.complexity, s.complexity),
+                (&mut m.functions, s.functions),
+                (&mut m.lines, s.lines),
+                (&mut m.code, s.code),
+                (&mut m.comments, s.comments),
+                (&mut m.blanks, s.blanks),
+                (&mut m.cognitive, s.cognitive),
+                (&mut m.max_nesting, s.max_nesting),
+            ] {
+                if v > 0 {
+                    *d = v;
+                }
+            }
+            if src.language.is_some() {
+                dst.language = src.language;
+            }
+            if src.maintainability.is_some() {
+                dst.maintainability = src.maintainability;
+            }
+        }
+        // A file in one input and a folder in the other: keep the first.
+        _ => {}
+    }
+}
+
+fn refresh_directory(node: &mut Node) {
+    let Some(children) = node.entries_mut() else {
+        return;
+    };
+    let mut metrics = Metrics::default();
+    let mut langs: HashMap<String, usize> = HashMap::new();
+    for c in children.iter_mut() {
+        refresh_directory(c);
+        metrics += &c.metrics;
+        if let Some(l) = &c.language {
+            *langs.entry(l.clone()).or_default() += c.metrics.loc;
+        }
+    }
+    let maintainability = Maintainability::below(children);
+    node.metrics = metrics;
+    node.language = dominant_language(&langs);
+    node.maintainability = maintainability;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+
+    const RYOIKI: &str = r#"{
+        "name": "proj", "path": ".", "kind": "directory",
+        "metrics": {"loc": 10, "complexity": 2, "functions": 1},
+        "language": "rust",
+        "children": [{
+            "name": "main.rs", "path": "main.rs", "kind": "file",
+            "metrics": {"loc": 10, "complexity": 2, "functions": 1},
+            "language": "rust", "children": null
+        }]
+    }"#;
+
+    const CODECHARTA: &str = r#"{
+        "checksum": "abc",
+        "data": {
+            "projectName": "sonar", "apiVersion": "1.3",
+            "nodes": [{"name": "root", "type": "Folder", "attributes": {}, "children": [
+                {"name": "main.rs", "type": "File", "attributes": {"code_smell": 4}},
+                {"name": "lib.rs", "type": "File", "attributes": {"rloc": 5}}
+            ]}],
+            "attributeTypes": {"nodes": {}, "edges": {}}
+        }
+    }"#;
+
+    #[test]
+    fn test_parse_tree_detects_format() {
+        let own = parse_tree(RYOIKI.as_bytes()).expect("ryoiki tree");
+        assert_eq!(own.name, "proj");
+
+        let cc = parse_tree(CODECHARTA.as_bytes()).expect("codecharta tree");
+        assert_eq!(cc.name, "sonar");
+        assert_eq!(cc.metrics.loc, 5);
+    }
+
+    #[test]
+    fn test_parse_tree_gzip() {
+        let mut enc = GzEncoder::new(Vec::new(), Compression::default());
+        enc.write_all(CODECHARTA.as_bytes()).expect("compress");
+        let gz = enc.finish().expect("compress");
+        let cc = parse_tree(&gz).expect("gzip tree");
+        assert_eq!(cc.children.map(|c| c.len()), Some(2));
+
+        let len = CODECHARTA.len() as u64;
+        assert_eq!(gunzip(&gz, len).expect("at the limit").len() as u64, len);
+        assert!(matches!(gunzip(&gz, len - 1), Err(ImportError::TooLarge)));
+    }
+
+    #[test]
+    fn test_merge_trees_combines_by_path() {
+        let own = parse_tree(RYOIKI.as_bytes()).expect("ryoiki tree");
+        let cc = parse_tree(CODECHARTA.as_bytes()).expect("codecharta tree");
+        let merged = merge_trees(vec![own, cc]).expect("merged");
+        let children = merged.children.expect("children");
+
+        assert_eq!(merged.name, "proj");
+        assert_eq!(children.len(), 2);
+        assert_eq!(children[0].metrics.loc, 10);
+        assert_eq!(children[0].attributes.get("code_smell"), Some(&4.0));
+        assert_eq!(merged.metrics.loc, 15);
+    }
+}
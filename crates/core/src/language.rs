@@ -0,0 +1,859 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Normalizes a tokei language name (`C++`, `Dockerfile`, `Plain Text`) to
+/// the id used by the viewer palette (`cpp`, `docker`, `plain-text`).
+pub fn language_id(tokei_name: &str) -> String {
+    let id = match tokei_name {
+        "C++" | "C++ Header" | "Arduino C++" => "cpp",
+        "C Header" => "c",
+        "C#" => "csharp",
+        "F#" => "fsharp",
+        "Objective-C++" => "objective-cpp",
+        "Shell" | "BASH" | "Zsh" | "Fish" | "C Shell" | "Korn shell" => "shell",
+        "JSX" => "javascript",
+        "TSX" => "typescript",
+        "CSS" | "Sass" | "LESS" | "PostCSS" => "css",
+        "Dockerfile" => "docker",
+        "Makefile" => "make",
+        "Protocol Buffers" => "proto",
+        "Plain Text" => "text",
+        _ => return tokei_name.to_ascii_lowercase().replace(' ', "-"),
+    };
+    id.to_string()
+}
+
+/// Maps a file name to a palette id by extension. The scanner uses tokei's
+/// detection instead; this is the fallback where tokei is unavailable (wasm)
+/// or only a name is known (imported trees).
+pub fn language_from_path(p: &Path) -> Option<String> {
+    let ext = p
+        .extension()
+        .and_then(|s| s.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    let lang = match ext.as_str() {
+        "rs" => "rust",
+        "ts" | "tsx" | "mts" | "cts" => "typescript",
+        "js" | "jsx" | "mjs" | "cjs" => "javascript",
+        "json" => "json",
+        "toml" => "toml",
+        "md" | "markdown" => "markdown",
+        "py" | "pyw" => "python",
+        "java" => "java",
+        "go" => "go",
+        "cpp" | "cxx" | "cc" | "hpp" | "hxx" => "cpp",
+        "c" | "h" => "c",
+        "cs" => "csharp",
+        "php" => "php",
+        "rb" => "ruby",
+        "kt" | "kts" => "kotlin",
+        "swift" => "swift",
+        "scala" | "sc" => "scala",
+        "sh" | "bash" | "zsh" => "shell",
+        "ps1" | "psm1" | "psd1" => "powershell",
+        "html" | "htm" => "html",
+        "css" | "scss" | "sass" | "less" => "css",
+        "yaml" | "yml" => "yaml",
+        "xml" => "xml",
+        "svelte" => "svelte",
+        "vue" => "vue",
+        "sql" => "sql",
+        "dockerfile" => "docker",
+        _ => return None,
+    };
+    Some(lang.to_string())
+}
+
+/// How a language writes comments and string literals, for
+/// [`crate::metrics_calc::lex`].
+#[derive(Debug)]
+pub struct LexicalRules {
+    /// Comments that run to the end of the line, such as `//` or `#`.
+    pub line_comments: &'static [&'static str],
+    /// Whether a line comment has to start a word, as in shell, where `$#`
+    /// is no comment.
+    pub comment_starts_word: bool,
+    /// Comment delimiters such as `/*` and `*/`.
+    pub block_comments: &'static [(&'static str, &'static str)],
+    pub nested_comments: bool,
+    /// Comment delimiters that only count at the start of a line, such as
+    /// Ruby's `=begin` and `=end`.
+    pub line_blocks: &'static [(&'static str, &'static str)],
+    /// String delimiters, longest first, and whether such a string may span
+    /// lines.
+    pub strings: &'static [(&'static str, bool)],
+    /// Whether a backslash escapes the next character of a string.
+    pub escapes: bool,
+    /// String delimiters whose contents a backslash never escapes, such as
+    /// Go's backtick raw strings.
+    pub raw_strings: &'static [&'static str],
+}
+
+const C_LIKE: LexicalRules = LexicalRules {
+    line_comments: &["//"],
+    comment_starts_word: false,
+    block_comments: &[("/*", "*/")],
+    nested_comments: false,
+    line_blocks: &[],
+    strings: &[("\"", false), ("'", false)],
+    escapes: true,
+    raw_strings: &[],
+};
+
+const HASH_COMMENTS: LexicalRules = LexicalRules {
+    line_comments: &["#"],
+    block_comments: &[],
+    strings: &[],
+    ..C_LIKE
+};
+
+static C_FAMILY: LexicalRules = C_LIKE;
+static JS_LIKE: LexicalRules = LexicalRules {
+    strings: &[("\"", false), ("'", false), ("`", true)],
+    ..C_LIKE
+};
+static GO: LexicalRules = LexicalRules {
+    raw_strings: &["`"],
+    ..JS_LIKE
+};
+static TRIPLE_QUOTED: LexicalRules = LexicalRules {
+    nested_comments: true,
+    strings: &[("\"\"\"", true), ("\"", false), ("'", false)],
+    ..C_LIKE
+};
+static PHP: LexicalRules = LexicalRules {
+    line_comments: &["//", "#"],
+    strings: &[("\"", true), ("'", true)],
+    ..C_LIKE
+};
+static CSS: LexicalRules = LexicalRules {
+    line_comments: &[],
+    ..C_LIKE
+};
+static PYTHON: LexicalRules = LexicalRules {
+    strings: &[("\"\"\"", true), ("'''", true), ("\"", false), ("'", false)],
+    ..HASH_COMMENTS
+};
+static SHELL: LexicalRules = LexicalRules {
+    comment_starts_word: true,
+    strings: &[("\"", true), ("'", true)],
+    ..HASH_COMMENTS
+};
+static POWERSHELL: LexicalRules = LexicalRules {
+    block_comments: &[("<#", "#>")],
+    strings: &[("\"", true), ("'", true)],
+    // PowerShell escapes with a backtick.
+    escapes: false,
+    ..HASH_COMMENTS
+};
+static RUBY: LexicalRules = LexicalRules {
+    line_blocks: &[("=begin", "=end")],
+    strings: &[("\"", true), ("'", true), ("`", true)],
+    ..HASH_COMMENTS
+};
+static SQL: LexicalRules = LexicalRules {
+    line_comments: &["--"],
+    strings: &[("'", true), ("\"", true)],
+    escapes: false,
+    ..C_LIKE
+};
+static MARKUP: LexicalRules = LexicalRules {
+    line_comments: &[],
+    block_comments: &[("<!--", "-->")],
+    strings: &[],
+    escapes: false,
+    ..C_LIKE
+};
+static YAML: LexicalRules = LexicalRules {
+    comment_starts_word: true,
+    strings: &[("\"", false), ("'", false)],
+    ..HASH_COMMENTS
+};
+static TOML: LexicalRules = LexicalRules {
+    strings: &[("\"\"\"", true), ("'''", true), ("\"", false), ("'", false)],
+    ..HASH_COMMENTS
+};
+static HASH_ONLY: LexicalRules = HASH_COMMENTS;
+
+/// The comment and string rules for a palette id. Rust has its own lexer,
+/// [`crate::metrics_calc::rust_spans`], and languages without rules are
+/// sanitized as C.
+pub fn lexical_rules(id: &str) -> Option<&'static LexicalRules> {
+    let rules = match id {
+        "c" | "cpp" | "java" | "csharp" | "objective-c" | "objective-cpp" | "proto" => &C_FAMILY,
+        "javascript" | "typescript" => &JS_LIKE,
+        "go" => &GO,
+        "kotlin" | "swift" | "scala" => &TRIPLE_QUOTED,
+        "php" => &PHP,
+        "css" => &CSS,
+        "python" => &PYTHON,
+        "shell" => &SHELL,
+        "powershell" => &POWERSHELL,
+        "ruby" => &RUBY,
+        "sql" => &SQL,
+        "html" | "xml" | "vue" | "svelte" => &MARKUP,
+        "yaml" => &YAML,
+        "toml" => &TOML,
+        "docker" | "make" => &HASH_ONLY,
+        _ => return None,
+    };
+    Some(rules)
+}
+
+/// What Halstead measures count in a language, for
+/// [`crate::metrics_calc::halstead_collect`]: its operators and the reserved
+/// words, which are neither operators nor operands.
+#[derive(Debug)]
+pub struct OperatorSet {
+    /// Operators of more than one character; the longest match wins.
+    pub operators: &'static [&'static str],
+    /// Single-character operators.
+    pub symbols: &'static str,
+    pub keywords: &'static [&'static str],
+}
+
+static RUST_OPERATORS: OperatorSet = OperatorSet {
+    operators: &[
+        "<<=", ">>=", "==", "!=", "<=", ">=", "&&", "||", "+=", "-=", "*=", "/=", "%=", "&=", "|=",
+        "^=", "<<", ">>", "->", "=>", "::",
+    ],
+    symbols: "+-*/%=&|^!<>.?:@",
+    keywords: &[
+        "fn", "let", "mut", "if", "else", "match", "for", "while", "loop", "return", "break",
+        "continue", "struct", "enum", "impl", "trait", "use", "mod", "pub", "crate", "super",
+        "Self", "self", "in", "as", "where", "const", "static", "ref", "type", "true", "false",
+        "await", "async", "move", "unsafe",
+    ],
+};
+static C_OPERATORS: OperatorSet = OperatorSet {
+    operators: &[
+        "<<=", ">>=", "==", "!=", "<=", ">=", "&&", "||", "++", "--", "+=", "-=", "*=", "/=", "%=",
+        "&=", "|=", "^=", "<<", ">>", "->",
+    ],
+    symbols: "+-*/%=&|^!<>.?:~",
+    keywords: &[
+        "auto", "break", "case", "char", "const", "continue", "default", "do", "double", "else",
+        "enum", "extern", "float", "for", "goto", "if", "inline", "int", "long", "register",
+        "restrict", "return", "short", "signed", "sizeof", "static", "struct", "switch", "typedef",
+        "union", "unsigned", "void", "volatile", "while", "bool", "true", "false",
+    ],
+};
+static CPP_OPERATORS: OperatorSet = OperatorSet {
+    operators: &[
+        "<<=", ">>=", "<=>", "==", "!=", "<=", ">=", "&&", "||", "++", "--", "+=", "-=", "*=",
+        "/=", "%=", "&=", "|=", "^=", "<<", ">>", "->*", "->", ".*", "::",
+    ],
+    symbols: "+-*/%=&|^!<>.?:~",
+    keywords: &[
+        "auto",
+        "break",
+        "case",
+        "catch",
+        "char",
+        "class",
+        "const",
+        "constexpr",
+        "continue",
+        "default",
+        "delete",
+        "do",
+        "double",
+        "else",
+        "enum",
+        "explicit",
+        "extern",
+        "false",
+        "float",
+        "for",
+        "friend",
+        "goto",
+        "if",
+        "inline",
+        "int",
+        "long",
+        "mutable",
+        "namespace",
+        "new",
+        "noexcept",
+        "nullptr",
+        "operator",
+        "private",
+        "protected",
+        "public",
+        "return",
+        "short",
+        "signed",
+        "sizeof",
+        "static",
+        "struct",
+        "switch",
+        "template",
+        "this",
+        "throw",
+        "true",
+        "try",
+        "typedef",
+        "typename",
+        "union",
+        "unsigned",
+        "using",
+        "virtual",
+        "void",
+        "volatile",
+        "while",
+        "bool",
+        "override",
+        "final",
+    ],
+};
+static JAVA_OPERATORS: OperatorSet = OperatorSet {
+    operators: &[
+        ">>>=", "<<=", ">>=", ">>>", "==", "!=", "<=", ">=", "&&", "||", "++", "--", "+=", "-=",
+        "*=", "/=", "%=", "&=", "|=", "^=", "<<", ">>", "->", "::",
+    ],
+    symbols: "+-*/%=&|^!<>.?:~@",
+    keywords: &[
+        "abstract",
+        "assert",
+        "boolean",
+        "break",
+        "byte",
+        "case",
+        "catch",
+        "char",
+        "class",
+        "const",
+        "continue",
+        "default",
+        "do",
+        "double",
+        "else",
+        "enum",
+        "extends",
+        "final",
+        "finally",
+        "float",
+        "for",
+        "if",
+        "implements",
+        "import",
+        "instanceof",
+        "int",
+        "interface",
+        "long",
+        "native",
+        "new",
+        "package",
+        "private",
+        "protected",
+        "public",
+        "return",
+        "short",
+        "static",
+        "super",
+        "switch",
+        "synchronized",
+        "this",
+        "throw",
+        "throws",
+        "try",
+        "void",
+        "volatile",
+        "while",
+        "var",
+        "record",
+        "yield",
+        "true",
+        "false",
+        "null",
+    ],
+};
+static CSHARP_OPERATORS: OperatorSet = OperatorSet {
+    operators: &[
+        "<<=", ">>=", "??=", "==", "!=", "<=", ">=", "&&", "||", "++", "--", "+=", "-=", "*=",
+        "/=", "%=", "&=", "|=", "^=", "<<", ">>", "->", "=>", "??", "?.", "::",
+    ],
+    symbols: "+-*/%=&|^!<>.?:~",
+    keywords: &[
+        "abstract",
+        "as",
+        "async",
+        "await",
+        "base",
+        "bool",
+        "break",
+        "byte",
+        "case",
+        "catch",
+        "char",
+        "class",
+        "const",
+        "continue",
+        "decimal",
+        "default",
+        "delegate",
+        "do",
+        "double",
+        "else",
+        "enum",
+        "event",
+        "explicit",
+        "extern",
+        "false",
+        "finally",
+        "float",
+        "for",
+        "foreach",
+        "get",
+        "if",
+        "implicit",
+        "in",
+        "int",
+        "interface",
+        "internal",
+        "is",
+        "lock",
+        "long",
+        "namespace",
+        "new",
+        "null",
+        "object",
+        "out",
+        "override",
+        "params",
+        "private",
+        "protected",
+        "public",
+        "readonly",
+        "ref",
+        "return",
+        "sealed",
+        "set",
+        "short",
+        "static",
+        "string",
+        "struct",
+        "switch",
+        "this",
+        "throw",
+        "true",
+        "try",
+        "typeof",
+        "uint",
+        "ulong",
+        "using",
+        "var",
+        "virtual",
+        "void",
+        "while",
+        "yield",
+    ],
+};
+static JS_OPERATORS: OperatorSet = OperatorSet {
+    operators: &[
+        ">>>=", "===", "!==", "**=", "<<=", ">>=", ">>>", "??=", "&&=", "||=", "...", "==", "!=",
+        "<=", ">=", "&&", "||", "??", "?.", "++", "--", "+=", "-=", "*=", "/=", "%=", "&=", "|=",
+        "^=", "**", "<<", ">>", "=>",
+    ],
+    symbols: "+-*/%=&|^!<>.?:~",
+    keywords: &[
+        "async",
+        "await",
+        "break",
+        "case",
+        "catch",
+        "class",
+        "const",
+        "continue",
+        "debugger",
+        "default",
+        "delete",
+        "do",
+        "else",
+        "export",
+        "extends",
+        "false",
+        "finally",
+        "for",
+        "function",
+        "if",
+        "import",
+        "in",
+        "instanceof",
+        "let",
+        "new",
+        "null",
+        "of",
+        "return",
+        "static",
+        "super",
+        "switch",
+        "this",
+        "throw",
+        "true",
+        "try",
+        "typeof",
+        "undefined",
+        "var",
+        "void",
+        "while",
+        "yield",
+        "interface",
+        "type",
+        "enum",
+        "implements",
+        "private",
+        "protected",
+        "public",
+        "readonly",
+        "abstract",
+        "as",
+        "declare",
+        "keyof",
+        "namespace",
+    ],
+};
+static GO_OPERATORS: OperatorSet = OperatorSet {
+    operators: &[
+        "<<=", ">>=", "&^=", "...", "==", "!=", "<=", ">=", "&&", "||", "++", "--", "+=", "-=",
+        "*=", "/=", "%=", "&=", "|=", "^=", "<<", ">>", "&^", ":=", "<-",
+    ],
+    symbols: "+-*/%=&|^!<>.:",
+    keywords: &[
+        "break",
+        "case",
+        "chan",
+        "const",
+        "continue",
+        "default",
+        "defer",
+        "else",
+        "fallthrough",
+        "for",
+        "func",
+        "go",
+        "goto",
+        "if",
+        "import",
+        "interface",
+        "map",
+        "package",
+        "range",
+        "return",
+        "select",
+        "struct",
+        "switch",
+        "type",
+        "var",
+        "true",
+        "false",
+        "nil",
+    ],
+};
+static KOTLIN_OPERATORS: OperatorSet = OperatorSet {
+    operators: &[
+        "===", "!==", "..<", "==", "!=", "<=", ">=", "&&", "||", "++", "--", "+=", "-=", "*=",
+        "/=", "%=", "->", "::", "?.", "?:", "!!", "..",
+    ],
+    symbols: "+-*/%=!<>.?:@",
+    keywords: &[
+        "as",
+        "break",
+        "class",
+        "continue",
+        "do",
+        "else",
+        "false",
+        "for",
+        "fun",
+        "if",
+        "in",
+        "interface",
+        "is",
+        "null",
+        "object",
+        "package",
+        "return",
+        "super",
+        "this",
+        "throw",
+        "true",
+        "try",
+        "typealias",
+        "val",
+        "var",
+        "when",
+        "while",
+        "by",
+        "catch",
+        "constructor",
+        "companion",
+        "data",
+        "enum",
+        "finally",
+        "import",
+        "init",
+        "internal",
+        "open",
+        "override",
+        "private",
+        "protected",
+        "public",
+        "sealed",
+        "suspend",
+    ],
+};
+static SWIFT_OPERATORS: OperatorSet = OperatorSet {
+    operators: &[
+        "===", "!==", "...", "..<", "<<=", ">>=", "==", "!=", "<=", ">=", "&&", "||", "+=", "-=",
+        "*=", "/=", "%=", "&=", "|=", "^=", "<<", ">>", "->", "??", "?.",
+    ],
+    symbols: "+-*/%=&|^!<>.?:~@",
+    keywords: &[
+        "as",
+        "associatedtype",
+        "break",
+        "case",
+        "catch",
+        "class",
+        "continue",
+        "default",
+        "defer",
+        "deinit",
+        "do",
+        "else",
+        "enum",
+        "extension",
+        "fallthrough",
+        "false",
+        "fileprivate",
+        "for",
+        "func",
+        "guard",
+        "if",
+        "import",
+        "in",
+        "init",
+        "inout",
+        "internal",
+        "is",
+        "let",
+        "nil",
+        "open",
+        "operator",
+        "private",
+        "protocol",
+        "public",
+        "repeat",
+        "return",
+        "self",
+        "Self",
+        "static",
+        "struct",
+        "subscript",
+        "super",
+        "switch",
+        "throw",
+        "throws",
+        "true",
+        "try",
+        "typealias",
+        "var",
+        "where",
+        "while",
+    ],
+};
+static SCALA_OPERATORS: OperatorSet = OperatorSet {
+    operators: &[
+        "==", "!=", "<=", ">=", "&&", "||", "+=", "-=", "*=", "/=", "<<", ">>", "=>", "<-", "->",
+        "::", "++", ":+", "+:",
+    ],
+    symbols: "+-*/%=&|^!<>.:~@",
+    keywords: &[
+        "abstract",
+        "case",
+        "catch",
+        "class",
+        "def",
+        "do",
+        "else",
+        "extends",
+        "false",
+        "final",
+        "finally",
+        "for",
+        "forSome",
+        "if",
+        "implicit",
+        "import",
+        "lazy",
+        "match",
+        "new",
+        "null",
+        "object",
+        "override",
+        "package",
+        "private",
+        "protected",
+        "return",
+        "sealed",
+        "super",
+        "this",
+        "throw",
+        "trait",
+        "true",
+        "try",
+        "type",
+        "val",
+        "var",
+        "while",
+        "with",
+        "yield",
+        "given",
+        "using",
+        "enum",
+        "then",
+    ],
+};
+static PHP_OPERATORS: OperatorSet = OperatorSet {
+    operators: &[
+        "===", "!==", "<=>", "**=", "??=", "<<=", ">>=", "==", "!=", "<>", "<=", ">=", "&&", "||",
+        "??", "?->", "++", "--", "+=", "-=", "*=", "/=", ".=", "%=", "&=", "|=", "^=", "**", "<<",
+        ">>", "->", "=>", "::",
+    ],
+    symbols: "+-*/%=&|^!<>.?:~@",
+    keywords: &[
+        "abstract",
+        "and",
+        "array",
+        "as",
+        "break",
+        "case",
+        "catch",
+        "class",
+        "clone",
+        "const",
+        "continue",
+        "declare",
+        "default",
+        "do",
+        "echo",
+        "else",
+        "elseif",
+        "empty",
+        "enum",
+        "extends",
+        "false",
+        "final",
+        "finally",
+        "fn",
+        "for",
+        "foreach",
+        "function",
+        "global",
+        "if",
+        "implements",
+        "include",
+        "instanceof",
+        "interface",
+        "isset",
+        "list",
+        "match",
+        "namespace",
+        "new",
+        "null",
+        "or",
+        "private",
+        "protected",
+        "public",
+        "readonly",
+        "require",
+        "require_once",
+        "return",
+        "static",
+        "switch",
+        "this",
+        "throw",
+        "trait",
+        "true",
+        "try",
+        "unset",
+        "use",
+        "var",
+        "while",
+        "xor",
+        "yield",
+    ],
+};
+static PYTHON_OPERATORS: OperatorSet = OperatorSet {
+    operators: &[
+        "**=", "//=", "<<=", ">>=", "==", "!=", "<=", ">=", "+=", "-=", "*=", "/=", "%=", "&=",
+        "|=", "^=", "@=", "**", "//", "<<", ">>", "->", ":=",
+    ],
+    symbols: "+-*/%=&|^<>.:~@",
+    keywords: &[
+        "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class",
+        "continue", "def", "del", "elif", "else", "except", "finally", "for", "from", "global",
+        "if", "import", "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return",
+        "try", "while", "with", "yield", "self",
+    ],
+};
+static RUBY_OPERATORS: OperatorSet = OperatorSet {
+    operators: &[
+        "<=>", "===", "**=", "<<=", ">>=", "&&=", "||=", "...", "==", "!=", "=~", "!~", "<=", ">=",
+        "&&", "||", "+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=", "**", "<<", ">>", "&.", "..",
+        "::", "=>", "->",
+    ],
+    symbols: "+-*/%=&|^!<>.?:~",
+    keywords: &[
+        "BEGIN", "END", "alias", "and", "begin", "break", "case", "class", "def", "defined", "do",
+        "else", "elsif", "end", "ensure", "false", "for", "if", "in", "module", "next", "nil",
+        "not", "or", "redo", "rescue", "retry", "return", "self", "super", "then", "true", "undef",
+        "unless", "until", "when", "while", "yield",
+    ],
+};
+
+/// The Halstead operators and keywords for a palette id; `None` for
+/// languages that are not code Halstead measures make sense for.
+pub fn operator_set(id: &str) -> Option<&'static OperatorSet> {
+    let set = match id {
+        "rust" => &RUST_OPERATORS,
+        "c" | "objective-c" => &C_OPERATORS,
+        "cpp" | "objective-cpp" => &CPP_OPERATORS,
+        "java" => &JAVA_OPERATORS,
+        "csharp" => &CSHARP_OPERATORS,
+        "javascript" | "typescript" => &JS_OPERATORS,
+        "go" => &GO_OPERATORS,
+        "kotlin" => &KOTLIN_OPERATORS,
+        "swift" => &SWIFT_OPERATORS,
+        "scala" => &SCALA_OPERATORS,
+        "php" => &PHP_OPERATORS,
+        "python" => &PYTHON_OPERATORS,
+        "ruby" => &RUBY_OPERATORS,
+        _ => return None,
+    };
+    Some(set)
+}
+
+/// The language with the most lines, used to label directories.
+pub fn dominant_language(map: &HashMap<String, usize>) -> Option<String> {
+    let mut v: Vec<(&String, &usize)> = map.iter().collect();
+    // Ties go to the alphabetically first name so repeated scans agree.
+    v.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+    v.first().map(|(k, _)| (*k).clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_language_id_matches_palette_ids() {
+        assert_eq!(language_id("Rust"), "rust");
+        assert_eq!(language_id("C++ Header"), "cpp");
+        assert_eq!(language_id("Dockerfile"), "docker");
+        assert_eq!(language_id("TSX"), "typescript");
+        assert_eq!(language_id("Visual Basic"), "visual-basic");
+    }
+}
@@ -0,0 +1,64 @@
+//! Scanning pipeline shared by the `ryoiki` binary, its build script and the
+//! HTTP server.
+//!
+//! [`run_scan`] walks a [`ScanConfig::root`] and returns a [`ScanReport`]
+//! without touching the filesystem beyond reading sources. Persisting the
+//! report is an explicit, separate step via [`write_outputs`]. Set
+//! [`ScanConfig::cache_path`] to reuse per-file results across scans, and
+//! [`ScanConfig::history`] to attach git activity to the tree. Set
+//! [`ScanConfig::revision`] to scan a commit from the git object database
+//! instead of the working tree, and [`run_timeline`] to scan a run of them.
+//! [`SnapshotStore`] keeps past scans for trends.
+
+pub mod analysis;
+#[cfg(feature = "scan")]
+pub mod cache;
+pub mod codecharta;
+pub mod config;
+#[cfg(feature = "scan")]
+pub mod coupling;
+pub mod diff;
+#[cfg(feature = "scan")]
+pub mod history;
+pub mod hotspots;
+pub mod import;
+pub mod language;
+pub mod metrics_calc;
+pub mod report;
+#[cfg(feature = "scan")]
+mod revision;
+mod rust_ast;
+#[cfg(feature = "scan")]
+pub mod scan;
+pub mod snapshots;
+#[cfg(feature = "tree-sitter")]
+mod syntax_tree;
+#[cfg(feature = "scan")]
+pub mod timeline;
+
+pub use codecharta::{CcProject, to_codecharta};
+pub use config::{
+    CONFIG_FILES, ConfigError, ConfigOverrides, CouplingOptions, DEFAULT_EXCLUDES,
+    DEFAULT_HISTORY_DAYS, HistoryOptions, ScanConfig, ScanDepth, ScanOutputs, SnapshotOptions,
+    ToolsConfig,
+};
+#[cfg(feature = "scan")]
+pub use coupling::coupling_edges;
+pub use diff::{
+    AttributeChange, DeltaNode, DeltaStatus, FileChange, MetricsDelta, TreeDiff, delta_tree,
+    diff_trees,
+};
+#[cfg(feature = "scan")]
+pub use history::{History, HistoryError, read_history};
+pub use hotspots::score_hotspots;
+pub use import::{ImportError, merge_trees, parse_tree};
+pub use report::{
+    AdvancedMetrics, AuditCount, CacheStats, CategoryTotals, CouplingEdge, Diagnostic,
+    FunctionSpan, GitMetrics, Hotspot, LanguageTotals, Maintainability, Metrics, MetricsSummary,
+    Node, ScanCommit, ScanReport, TimelinePoint, Totals, write_outputs,
+};
+#[cfg(feature = "scan")]
+pub use scan::{ScanError, run_scan};
+pub use snapshots::{SeriesPoint, Snapshot, SnapshotMeta, SnapshotStore};
+#[cfg(feature = "scan")]
+pub use timeline::{TimelineOptions, TimelineStep, run_timeline};
@@ -0,0 +1,893 @@
+use crate::language::{LexicalRules, OperatorSet, lexical_rules};
+use std::collections::HashSet;
+use std::ops::Range;
+
+pub fn sanitize(src: &str) -> String {
+    let mut out = String::with_capacity(src.len());
+    let mut chars = src.chars().peekable();
+    let mut in_line = false;
+    let mut in_block = false;
+    let mut in_str = false;
+    let mut in_char = false;
+    let mut esc = false;
+    while let Some(ch) = chars.next() {
+        if in_line {
+            if ch == '\n' {
+                in_line = false;
+                out.push('\n');
+            }
+            continue;
+        }
+        if in_block {
+            if ch == '*'
+                && let Some('/') = chars.peek().copied()
+            {
+                chars.next();
+                in_block = false;
+            }
+            continue;
+        }
+        if in_str {
+            if esc {
+                esc = false;
+            } else if ch == '\\' {
+                esc = true;
+            } else if ch == '"' {
+                in_str = false;
+            }
+            continue;
+        }
+        if in_char {
+            if esc {
+                esc = false;
+            } else if ch == '\\' {
+                esc = true;
+            } else if ch == '\'' {
+                in_char = false;
+            }
+            continue;
+        }
+        if ch == '/' {
+            match chars.peek().copied() {
+                Some('/') => {
+                    chars.next();
+                    in_line = true;
+                    continue;
+                }
+                Some('*') => {
+                    chars.next();
+                    in_block = true;
+                    continue;
+                }
+                _ => {}
+            }
+        }
+        if ch == '"' {
+            in_str = true;
+            continue;
+        }
+        if ch == '\'' {
+            in_char = true;
+            continue;
+        }
+        out.push(ch);
+    }
+    out
+}
+
+/// What a [`SourceSpan`] holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpanKind {
+    Code,
+    /// A line, block or doc comment.
+    Comment,
+    /// A string, byte string, raw string or character literal.
+    Literal,
+}
+
+/// A byte range of source text and what it holds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceSpan {
+    pub kind: SpanKind,
+    pub range: Range<usize>,
+}
+
+/// Splits Rust source into code, comment and literal spans that cover all
+/// of `src` in order.
+///
+/// Unlike [`sanitize`], it knows lifetimes and loop labels (`'a`,
+/// `'static`), raw strings (`r#"..."#`), byte and C strings (`b"..."`,
+/// `br"..."`, `c"..."`), byte literals and nested block comments. A comment
+/// or literal that is not closed runs to the end of `src`.
+pub fn rust_spans(src: &str) -> Vec<SourceSpan> {
+    let b = src.as_bytes();
+    split_spans(src, |i| match b[i] {
+        b'/' if b.get(i + 1) == Some(&b'/') => Some((SpanKind::Comment, line_end(b, i))),
+        b'/' if b.get(i + 1) == Some(&b'*') => {
+            Some((SpanKind::Comment, delimited_end(b, i, ("/*", "*/"), true)))
+        }
+        b'"' => Some((SpanKind::Literal, quoted_end(b, i + 1, "\"", true, true))),
+        b'\'' => char_end(src, i).map(|end| (SpanKind::Literal, end)),
+        b'r' | b'b' | b'c' if !continues_ident(b, i) => {
+            prefixed_literal_end(src, i).map(|end| (SpanKind::Literal, end))
+        }
+        _ => None,
+    })
+}
+
+/// Splits source into code, comment and literal spans by the comment and
+/// string rules of its language. Like [`rust_spans`], the spans cover all of
+/// `src` and unclosed comments and multi-line strings run to the end.
+pub fn lex(src: &str, rules: &LexicalRules) -> Vec<SourceSpan> {
+    let b = src.as_bytes();
+    split_spans(src, |i| {
+        let rest = &b[i..];
+        if i == 0 || b[i - 1] == b'\n' {
+            for &(open, close) in rules.line_blocks {
+                if rest.starts_with(open.as_bytes()) {
+                    return Some((SpanKind::Comment, line_block_end(b, i, close)));
+                }
+            }
+        }
+        for &delims in rules.block_comments {
+            if rest.starts_with(delims.0.as_bytes()) {
+                let end = delimited_end(b, i, delims, rules.nested_comments);
+                return Some((SpanKind::Comment, end));
+            }
+        }
+        let word_start = i == 0 || b[i - 1].is_ascii_whitespace();
+        for prefix in rules.line_comments {
+            if rest.starts_with(prefix.as_bytes()) && (word_start || !rules.comment_starts_word) {
+                return Some((SpanKind::Comment, line_end(b, i)));
+            }
+        }
+        for &(quote, multiline) in rules.strings {
+            if rest.starts_with(quote.as_bytes()) {
+                let escapes = rules.escapes && !rules.raw_strings.contains(&quote);
+                let end = quoted_end(b, i + quote.len(), quote, multiline, escapes);
+                return Some((SpanKind::Literal, end));
+            }
+        }
+        None
+    })
+}
+
+/// The code of `lang`, a palette id, with comments and string literals
+/// removed except for their line breaks, so line numbers stay. Languages
+/// without [`lexical_rules`] go through the C-style [`sanitize`].
+pub fn sanitize_language(lang: Option<&str>, src: &str) -> String {
+    let spans = match lang {
+        Some("rust") => rust_spans(src),
+        Some(id) => match lexical_rules(id) {
+            Some(rules) => lex(src, rules),
+            None => return sanitize(src),
+        },
+        None => return sanitize(src),
+    };
+    code_only(src, spans)
+}
+
+/// [`sanitize`] for Rust, built on [`rust_spans`].
+pub fn sanitize_rust(src: &str) -> String {
+    code_only(src, rust_spans(src))
+}
+
+fn code_only(src: &str, spans: Vec<SourceSpan>) -> String {
+    let mut out = String::with_capacity(src.len());
+    for span in spans {
+        let text = &src[span.range];
+        match span.kind {
+            SpanKind::Code => out.push_str(text),
+            SpanKind::Comment | SpanKind::Literal => {
+                out.extend(text.chars().filter(|&c| c == '\n'));
+            }
+        }
+    }
+    out
+}
+
+/// Runs `next` at each byte that is not inside a comment or literal; it
+/// returns the kind and end of a comment or literal starting there.
+fn split_spans(
+    src: &str,
+    mut next: impl FnMut(usize) -> Option<(SpanKind, usize)>,
+) -> Vec<SourceSpan> {
+    let len = src.len();
+    let mut spans = Vec::new();
+    let mut code_start = 0usize;
+    let mut i = 0usize;
+    while i < len {
+        let Some((kind, end)) = next(i) else {
+            i += 1;
+            continue;
+        };
+        if code_start < i {
+            spans.push(SourceSpan {
+                kind: SpanKind::Code,
+                range: code_start..i,
+            });
+        }
+        spans.push(SourceSpan {
+            kind,
+            range: i..end,
+        });
+        i = end;
+        code_start = end;
+    }
+    if code_start < len {
+        spans.push(SourceSpan {
+            kind: SpanKind::Code,
+            range: code_start..len,
+        });
+    }
+    spans
+}
+
+/// Whether the byte at `i` is inside an identifier, so an `r`, `b` or `c`
+/// there is no literal prefix.
+fn continues_ident(b: &[u8], i: usize) -> bool {
+    i > 0 && (b[i - 1].is_ascii_alphanumeric() || b[i - 1] == b'_' || b[i - 1] >= 0x80)
+}
+
+fn line_end(b: &[u8], i: usize) -> usize {
+    b[i..]
+        .iter()
+        .position(|&c| c == b'\n')
+        .map_or(b.len(), |p| i + p)
+}
+
+/// The end of the comment opened at `i`, through the line that starts with
+/// `close`.
+fn line_block_end(b: &[u8], i: usize, close: &str) -> usize {
+    let mut j = line_end(b, i);
+    while j < b.len() {
+        if b[j + 1..].starts_with(close.as_bytes()) {
+            return line_end(b, j + 1);
+        }
+        j = line_end(b, j + 1);
+    }
+    b.len()
+}
+
+/// The end of the comment opened at `i`.
+fn delimited_end(b: &[u8], i: usize, (open, close): (&str, &str), nested: bool) -> usize {
+    let mut depth = 1usize;
+    let mut j = i + open.len();
+    while j < b.len() {
+        if b[j..].starts_with(close.as_bytes()) {
+            j += close.len();
+            depth -= 1;
+            if depth == 0 {
+                return j;
+            }
+        } else if nested && b[j..].starts_with(open.as_bytes()) {
+            j += open.len();
+            depth += 1;
+        } else {
+            j += 1;
+        }
+    }
+    b.len()
+}
+
+/// The end of a string closed by `quote` whose contents start at `j`. A
+/// single-line string that is not closed ends before the line break.
+fn quoted_end(b: &[u8], mut j: usize, quote: &str, multiline: bool, escapes: bool) -> usize {
+    while j < b.len() {
+        if escapes && b[j] == b'\\' {
+            j += 2;
+        } else if b[j..].starts_with(quote.as_bytes()) {
+            return j + quote.len();
+        } else if !multiline && b[j] == b'\n' {
+            return j;
+        } else {
+            j += 1;
+        }
+    }
+    b.len()
+}
+
+/// The end of the character literal whose quote is at `i`; `None` for a
+/// lifetime or label.
+fn char_end(src: &str, i: usize) -> Option<usize> {
+    let b = src.as_bytes();
+    if b.get(i + 1) == Some(&b'\\') {
+        // The quote, the backslash and the escaped character, then up to
+        // the closing quote for `\u{...}` and `\x..`.
+        let from = (i + 3).min(b.len());
+        let close = from + b[from..].iter().position(|&c| c == b'\'' || c == b'\n')?;
+        return (b[close] == b'\'').then_some(close + 1);
+    }
+    let c = src.get(i + 1..)?.chars().next()?;
+    let after = i + 1 + c.len_utf8();
+    (c != '\'' && b.get(after) == Some(&b'\'')).then_some(after + 1)
+}
+
+/// The end of a literal with a prefix starting at `i`: `b"..."`, `b'x'`,
+/// `c"..."`, or a raw string such as `r#"..."#` or `br"..."`.
+fn prefixed_literal_end(src: &str, i: usize) -> Option<usize> {
+    let b = src.as_bytes();
+    let j = if b[i] == b'r' { i } else { i + 1 };
+    match b.get(j) {
+        Some(b'"') => Some(quoted_end(b, j + 1, "\"", true, true)),
+        Some(b'\'') if b[i] == b'b' => char_end(src, j),
+        Some(b'r') => {
+            let hashes = b[j + 1..].iter().take_while(|&&c| c == b'#').count();
+            let open = j + 1 + hashes;
+            if b.get(open) != Some(&b'"') {
+                // A raw identifier such as `r#type`.
+                return None;
+            }
+            let close = (open + 1..b.len()).find(|&k| {
+                b[k] == b'"' && b[k + 1..].iter().take_while(|&&c| c == b'#').count() >= hashes
+            });
+            Some(close.map_or(b.len(), |k| k + 1 + hashes))
+        }
+        _ => None,
+    }
+}
+
+/// Counts `token` in `s` where it stands on its own. A token that starts or
+/// ends with an identifier character must not continue an identifier on that
+/// side, so `if` does not match in `diff`, `for` in `format` or `fn ` in
+/// `cfn `. Keywords, tokens made only of identifier characters and spaces,
+/// are also skipped after `.`, a field or property such as `x.match`, and
+/// after `r#`, a raw identifier; method tokens such as `unwrap_or(` still
+/// match in `x.unwrap_or(1)`.
+pub fn count_token(s: &str, token: &str) -> usize {
+    let Some(first) = token.chars().next() else {
+        return 0;
+    };
+    let b = s.as_bytes();
+    let t = token.as_bytes();
+    let word_start = is_ident_byte(t[0]);
+    let word_end = is_ident_byte(t[t.len() - 1]);
+    let keyword = t.iter().all(|&c| is_ident_byte(c) || c == b' ');
+    let mut count = 0;
+    let mut i = 0;
+    while let Some(pos) = s[i..].find(token) {
+        let at = i + pos;
+        let end = at + token.len();
+        let free_before = !word_start
+            || at == 0
+            || !(is_ident_byte(b[at - 1])
+                || keyword
+                    && (b[at - 1] == b'.' || (b[at - 1] == b'#' && at >= 2 && b[at - 2] == b'r')));
+        let free_after = !word_end || end == b.len() || !is_ident_byte(b[end]);
+        if free_before && free_after {
+            count += 1;
+            i = end;
+        } else {
+            i = at + first.len_utf8();
+        }
+    }
+    count
+}
+
+/// Letters, digits, `_`, `$` (JavaScript and PHP) and any non-ASCII byte.
+fn is_ident_byte(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_' || c == b'$' || c >= 0x80
+}
+
+pub fn count_assignments(s: &str) -> usize {
+    let mut c = 0usize;
+    let bytes = s.as_bytes();
+    let mut i = 0usize;
+    while i < bytes.len() {
+        let b = bytes[i];
+        if b == b'=' {
+            let prev = if i > 0 { bytes[i - 1] } else { b'\0' };
+            let next = if i + 1 < bytes.len() {
+                bytes[i + 1]
+            } else {
+                b'\0'
+            };
+            if prev != b'!'
+                && prev != b'<'
+                && prev != b'>'
+                && prev != b'='
+                && next != b'='
+                && next != b'>'
+            {
+                c += 1;
+            }
+            i += 1;
+            continue;
+        }
+        match b {
+            b'+' | b'-' | b'*' | b'/' | b'%' | b'&' | b'|' | b'^'
+                if i + 1 < bytes.len() && bytes[i + 1] == b'=' =>
+            {
+                c += 1;
+                i += 2;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    c
+}
+
+pub fn count_branches(s: &str) -> usize {
+    let mut c = 0usize;
+    let bytes = s.as_bytes();
+    let mut i = 0usize;
+    while i < bytes.len() {
+        if bytes[i] == b'(' {
+            let mut j = i;
+            if j == 0 {
+                i += 1;
+                continue;
+            }
+            j -= 1;
+            while j > 0 && (bytes[j] as char).is_ascii_whitespace() {
+                j -= 1;
+            }
+            if bytes[j] == b'!' {
+                j = j.saturating_sub(1);
+            }
+            let mut k = j;
+            while k > 0 && ((bytes[k] as char).is_ascii_alphanumeric() || bytes[k] == b'_') {
+                if k == 0 {
+                    break;
+                }
+                k -= 1;
+            }
+            let start = if (bytes[k] as char).is_ascii_alphanumeric() || bytes[k] == b'_' {
+                k
+            } else {
+                k + 1
+            };
+            if start <= j {
+                let ident = String::from_utf8_lossy(&bytes[start..=j]).to_string();
+                if ident != "fn" && ident != "if" && ident != "match" && ident != "loop" {
+                    c += 1;
+                }
+            }
+        }
+        i += 1;
+    }
+    c
+}
+
+pub fn count_conditionals(s: &str) -> usize {
+    count_token(s, "if") + count_token(s, "match") + count_token(s, "&&") + count_token(s, "||")
+}
+
+pub fn count_cyclomatic_decisions(s: &str) -> usize {
+    count_conditionals(s)
+}
+
+pub fn count_conditionals_js_like(s: &str) -> usize {
+    ["if", "switch", "case", "&&", "||", "for", "while"]
+        .iter()
+        .map(|t| count_token(s, t))
+        .sum()
+}
+
+pub fn count_conditionals_python(s: &str) -> usize {
+    ["if", "elif", "for", "while", "except", "and", "or"]
+        .iter()
+        .map(|t| count_token(s, t))
+        .sum()
+}
+
+/// Cognitive complexity and the deepest nesting of sanitized source in a
+/// brace language, for files without a syntax tree.
+///
+/// `if`, loops, `switch` / `match` and `catch` add 1 plus the number of such
+/// blocks around them, `else`, `else if`, `goto` and every run of like
+/// `&&` / `||` operators add 1. Nesting only counts blocks in braces, so a
+/// braceless C `if` body does not nest; ternaries, labeled jumps and
+/// lambdas are not recognized.
+pub fn cognitive_and_nesting(s: &str) -> (usize, usize) {
+    let mut cognitive = 0usize;
+    let mut max_nesting = 0usize;
+    // One entry per open brace: the keyword of the block it opened, if any.
+    let mut blocks: Vec<Option<&str>> = Vec::new();
+    let mut depth = 0usize;
+    let mut pending: Option<&str> = None;
+    let mut parens = 0usize;
+    let mut logical: Option<&str> = None;
+    let mut after_do = false;
+    let mut in_impl = false;
+    let mut prev_else = false;
+    let bytes = s.as_bytes();
+    let mut i = 0usize;
+    while i < bytes.len() {
+        let ch = bytes[i] as char;
+        if ch.is_ascii_alphabetic() || ch == '_' {
+            let mut j = i + 1;
+            while j < bytes.len() && (bytes[j].is_ascii_alphanumeric() || bytes[j] == b'_') {
+                j += 1;
+            }
+            let word = &s[i..j];
+            i = j;
+            let else_if = prev_else && word == "if";
+            prev_else = false;
+            match word {
+                _ if else_if => pending = Some("if"),
+                "else" => {
+                    cognitive += 1;
+                    pending = Some("else");
+                    prev_else = true;
+                }
+                "while" if after_do => {}
+                "for" if in_impl => {}
+                "if" | "for" | "foreach" | "while" | "loop" | "do" | "switch" | "match"
+                | "catch" => {
+                    cognitive += 1 + depth;
+                    pending = Some(word);
+                }
+                "goto" => cognitive += 1,
+                "impl" => in_impl = true,
+                _ => {}
+            }
+            after_do = false;
+            continue;
+        }
+        if !ch.is_ascii_whitespace() {
+            prev_else = false;
+            after_do = false;
+        }
+        match ch {
+            '{' => {
+                blocks.push(pending.take());
+                if blocks.last().is_some_and(Option::is_some) {
+                    depth += 1;
+                    max_nesting = max_nesting.max(depth);
+                }
+                logical = None;
+                in_impl = false;
+            }
+            '}' => {
+                if let Some(opened) = blocks.pop().flatten() {
+                    depth -= 1;
+                    after_do = opened == "do";
+                }
+                logical = None;
+            }
+            '(' => parens += 1,
+            ')' => parens = parens.saturating_sub(1),
+            ';' if parens == 0 => {
+                pending = None;
+                logical = None;
+                in_impl = false;
+            }
+            ',' => logical = None,
+            '&' | '|' if bytes.get(i + 1) == Some(&bytes[i]) => {
+                let op = &s[i..i + 2];
+                if logical != Some(op) {
+                    cognitive += 1;
+                }
+                logical = Some(op);
+                i += 2;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    (cognitive, max_nesting)
+}
+
+/// Halstead operator and operand counts of sanitized `s`, total and
+/// distinct, with `set` naming the language's operators and keywords.
+pub fn halstead_collect(
+    s: &str,
+    set: &OperatorSet,
+) -> (usize, usize, HashSet<String>, HashSet<String>) {
+    let kw: HashSet<&str> = set.keywords.iter().copied().collect();
+    let bytes = s.as_bytes();
+    let mut i = 0usize;
+    let mut ops_total = 0usize;
+    let mut operands_total = 0usize;
+    let mut ops_set: HashSet<String> = HashSet::new();
+    let mut operands_set: HashSet<String> = HashSet::new();
+    while i < bytes.len() {
+        let slice = &bytes[i..];
+        if let Some(m) = set
+            .operators
+            .iter()
+            .filter(|m| slice.starts_with(m.as_bytes()))
+            .max_by_key(|m| m.len())
+        {
+            ops_total += 1;
+            ops_set.insert(m.to_string());
+            i += m.len();
+            continue;
+        }
+        let ch = bytes[i] as char;
+        if set.symbols.contains(ch) {
+            ops_total += 1;
+            ops_set.insert(ch.to_string());
+            i += 1;
+            continue;
+        }
+        if ch.is_ascii_digit() {
+            let mut j = i + 1;
+            while j < bytes.len()
+                && ((bytes[j] as char).is_ascii_alphanumeric()
+                    || bytes[j] == b'.'
+                    || bytes[j] == b'_')
+            {
+                j += 1;
+            }
+            let lit = String::from_utf8_lossy(&bytes[i..j]).to_string();
+            operands_total += 1;
+            operands_set.insert(lit);
+            i = j;
+            continue;
+        }
+        if ch.is_ascii_alphabetic() || ch == '_' {
+            let mut j = i + 1;
+            while j < bytes.len()
+                && ((bytes[j] as char).is_ascii_alphanumeric() || bytes[j] == b'_')
+            {
+                j += 1;
+            }
+            let ident = String::from_utf8_lossy(&bytes[i..j]).to_string();
+            if !kw.contains(ident.as_str()) {
+                operands_total += 1;
+                operands_set.insert(ident);
+            }
+            i = j;
+            continue;
+        }
+        i += 1;
+    }
+    (ops_total, operands_total, ops_set, operands_set)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language::operator_set;
+
+    #[test]
+    fn test_sanitize() {
+        let code = r#"
+            // This is a comment
+            let x = 1; /* Block comment */
+            let s = "String // not a comment";
+        "#;
+        let cleaned = sanitize(code);
+        assert!(!cleaned.contains("This is a comment"));
+        assert!(!cleaned.contains("Block comment"));
+        assert!(cleaned.contains("let x = 1;"));
+        // Strings are stripped by sanitize
+        assert!(!cleaned.contains("String"));
+        // But the assignment structure remains (let s = ;)
+        assert!(cleaned.contains("let s = ;"));
+    }
+
+    fn kinds(src: &str) -> Vec<(SpanKind, &str)> {
+        rust_spans(src)
+            .into_iter()
+            .map(|span| (span.kind, &src[span.range]))
+            .collect()
+    }
+
+    #[test]
+    fn test_rust_spans_literals_and_lifetimes() {
+        use SpanKind::*;
+        assert_eq!(
+            kinds("fn f<'a>(x: &'a str) -> char { 'x' }"),
+            [
+                (Code, "fn f<'a>(x: &'a str) -> char { "),
+                (Literal, "'x'"),
+                (Code, " }"),
+            ]
+        );
+        assert_eq!(
+            kinds(r##"let s = r#"a "quoted" // b"#; let r#type = br"\";"##),
+            [
+                (Code, "let s = "),
+                (Literal, r##"r#"a "quoted" // b"#"##),
+                (Code, "; let r#type = "),
+                (Literal, r#"br"\""#),
+                (Code, ";"),
+            ]
+        );
+        assert_eq!(
+            kinds(r"'outer: loop { b'\''; '\u{1F600}'; 'é'; break 'outer; }"),
+            [
+                (Code, "'outer: loop { "),
+                (Literal, r"b'\''"),
+                (Code, "; "),
+                (Literal, r"'\u{1F600}'"),
+                (Code, "; "),
+                (Literal, "'é'"),
+                (Code, "; break 'outer; }"),
+            ]
+        );
+        assert_eq!(
+            kinds("a /* x /* y */ z */ b\"c\\\"d\" // e\nf"),
+            [
+                (Code, "a "),
+                (Comment, "/* x /* y */ z */"),
+                (Code, " "),
+                (Literal, "b\"c\\\"d\""),
+                (Code, " "),
+                (Comment, "// e"),
+                (Code, "\nf"),
+            ]
+        );
+        assert_eq!(kinds("x /* open"), [(Code, "x "), (Comment, "/* open")]);
+    }
+
+    #[test]
+    fn test_sanitize_rust_keeps_code_after_lifetimes() {
+        let code = "fn f<'a>(x: &'a str) -> bool {\n    /* a /* b */ if c */\n    if x.is_empty() { return r#\"if\"#.len() > 0; }\n    false\n}\n";
+        let cleaned = sanitize_rust(code);
+        assert_eq!(cleaned.lines().count(), code.lines().count());
+        assert_eq!(count_token(&cleaned, "if "), 1);
+        assert!(cleaned.contains("return .len() > 0;"));
+        // The generic sanitizer reads `'a>(x: &'` as a character literal.
+        assert!(!sanitize(code).contains("fn f<'a>(x: &'a str)"));
+    }
+
+    #[test]
+    fn test_sanitize_language_strips_comments_and_strings() {
+        let python = "def f(x):\n    \"\"\"if x: return\n    else\"\"\"\n    s = 'if' # if not\n    return s\n";
+        assert_eq!(
+            sanitize_language(Some("python"), python),
+            "def f(x):\n    \n\n    s =  \n    return s\n"
+        );
+        let shell = "echo $# '# no' # comment\nfor f in *; do :; done";
+        assert_eq!(
+            sanitize_language(Some("shell"), shell),
+            "echo $#  \nfor f in *; do :; done"
+        );
+        let sql = "SELECT 'a -- b' -- c\nFROM t /* if */";
+        assert_eq!(sanitize_language(Some("sql"), sql), "SELECT  \nFROM t ");
+        let html = "<p>it's</p><!-- if\n -->";
+        assert_eq!(sanitize_language(Some("html"), html), "<p>it's</p>\n");
+        let ruby = "x = 1\n=begin\nif y\n=end trailing\nputs \"#{x}\" # if\n";
+        assert_eq!(
+            sanitize_language(Some("ruby"), ruby),
+            "x = 1\n\n\n\nputs  \n"
+        );
+        let kotlin = "/* a /* b */ if */ val s = \"\"\"\nif\"\"\"";
+        assert_eq!(sanitize_language(Some("kotlin"), kotlin), " val s = \n");
+        // A backslash ends a Go raw string's contents; it escapes nothing.
+        let go = "var re = `C:\\path\\`\nfunc f() { if x {} }";
+        assert_eq!(
+            sanitize_language(Some("go"), go),
+            "var re = \nfunc f() { if x {} }"
+        );
+        // No rules: the C-style sanitizer.
+        assert_eq!(sanitize_language(Some("json"), "{\"a\": 1}"), "{: 1}");
+    }
+
+    #[test]
+    fn test_count_token_matches_whole_tokens() {
+        let cases: &[(&str, &str, usize)] = &[
+            ("let d = diff(a, b); verify(d); if d {}", "if", 1),
+            ("format!(\"{}\", before); for x in y {}", "for", 1),
+            ("to_lowercase(s); case 1:", "case", 1),
+            ("cfn f(); fn g() {} let h: fn(u8);", "fn ", 1),
+            ("x.match(re); r#match; match y {}", "match", 1),
+            ("$if = 1; _if(); if_then(); (if)", "if", 1),
+            ("débordement; for é", "for", 1),
+            ("a.unwrap(); b.unwrap_or(1); c.unwrap()", ".unwrap()", 2),
+            ("x.unwrap_or(1)", "unwrap_or(", 1),
+            ("a.unwrap_or_else(f); my_unwrap_or(1)", "unwrap_or(", 0),
+            (
+                "debug_assert!(a); assert!(b); assert_eq!(c, d)",
+                "assert!",
+                1,
+            ),
+            ("unsafe_code(); unsafe { x }; is_unsafe", "unsafe", 1),
+            ("a&&b && c", "&&", 2),
+            ("", "if", 0),
+        ];
+        for &(src, token, expected) in cases {
+            assert_eq!(
+                count_token(src, token),
+                expected,
+                "{:?} in {:?}",
+                token,
+                src
+            );
+        }
+    }
+
+    #[test]
+    fn test_conditionals_skip_identifiers() {
+        assert_eq!(
+            count_conditionals("if notify(diff) && verify { match m {} }"),
+            3
+        );
+        assert_eq!(
+            count_conditionals_js_like(
+                "const s = format(lowercase(x)); before(); forEach(f); obj.for(x);\n\
+                 switch (x) { case 1: for (;;) {} } while (a || b) {}"
+            ),
+            5
+        );
+        // `if` in `elif`, `or` in `for` and `and` in `band` used to count too.
+        assert_eq!(
+            count_conditionals_python(
+                "elif band or verify(x):\n    for i in orders: pass\nexcept:\n    y = a if b else c"
+            ),
+            5
+        );
+    }
+
+    #[test]
+    fn test_count_assignments() {
+        let code = "a = 1; b += 2; c == 3;";
+        // a = 1 (1)
+        // b += 2 (1)
+        // c == 3 (0)
+        assert_eq!(count_assignments(code), 2);
+    }
+
+    #[test]
+    fn test_count_branches() {
+        let code = "fn foo() {} fn bar() {}";
+        // foo() (1)
+        // bar() (1)
+        assert_eq!(count_branches(code), 2);
+    }
+
+    #[test]
+    fn test_count_conditionals() {
+        let code = "if x && y || z { match a {} }";
+        // if (1)
+        // && (1)
+        // || (1)
+        // match (1)
+        assert_eq!(count_conditionals(code), 4);
+    }
+
+    #[test]
+    fn test_cognitive_and_nesting() {
+        let code = "fn f(a: bool, b: bool) {\n\
+                    if a && b && !a || b {\n\
+                        for x in y { while a { g(); } }\n\
+                    } else if b {\n\
+                        match a { _ => {} }\n\
+                    } else { h(); }\n\
+                    }\nimpl T for S {}\n";
+        // if 1, &&-run 1, ||-run 1, for 2, while 3, else if 1, match 2,
+        // else 1; `impl ... for` is not a loop.
+        assert_eq!(cognitive_and_nesting(code), (12, 3));
+        let c = "int f(int x) {\n  do { x--; } while (x > 0);\n  for (;;) { if (x) goto end; }\nend:\n  return x;\n}\n";
+        // do 1, for 1, if 2, goto 1; the braceless `if` does not nest.
+        assert_eq!(cognitive_and_nesting(c), (5, 1));
+    }
+
+    #[test]
+    fn test_halstead() {
+        let rust = operator_set("rust").expect("rust operators");
+        let code = "let a = b + c;";
+        let (ops, operands, ops_unique, operands_unique) = halstead_collect(code, rust);
+
+        // Operators: = (from single), + (from single)
+        // Operands: a, b, c
+        // Keywords 'let' is ignored.
+
+        assert_eq!(ops, 2); // = and +
+        assert_eq!(operands, 3); // a, b, c
+        assert_eq!(ops_unique.len(), 2);
+        assert_eq!(operands_unique.len(), 3);
+    }
+
+    #[test]
+    fn test_halstead_uses_the_language_operators() {
+        let java = operator_set("java").expect("java operators");
+        let (ops, operands, ops_unique, _) =
+            halstead_collect("public int f(int x) { return x >>> 1; }", java);
+        // `>>>` is one operator; `public`, `int` and `return` are keywords.
+        assert_eq!((ops, operands), (1, 4));
+        assert!(ops_unique.contains(">>>"));
+        let php = operator_set("php").expect("php operators");
+        let (ops, _, ops_unique, _) = halstead_collect("$a === $b ?? $c;", php);
+        assert_eq!(ops, 2);
+        assert!(ops_unique.contains("===") && ops_unique.contains("??"));
+        assert!(operator_set("markdown").is_none());
+    }
+}
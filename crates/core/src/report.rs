@@ -0,0 +1,454 @@
+use crate::analysis::RustItems;
+use crate::codecharta::to_codecharta;
+use crate::config::ScanOutputs;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::ops::AddAssign;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Metrics {
+    pub loc: usize,
+    pub complexity: usize,
+    pub functions: usize,
+    /// tokei's line breakdown; `lines = code + comments + blanks`. Files tokei
+    /// does not recognize count every non-blank line as code.
+    #[serde(default)]
+    pub lines: usize,
+    #[serde(default)]
+    pub code: usize,
+    #[serde(default)]
+    pub comments: usize,
+    #[serde(default)]
+    pub blanks: usize,
+    /// SonarSource cognitive complexity; see
+    /// [`crate::analysis::SyntaxAnalysis`].
+    #[serde(default)]
+    pub cognitive: usize,
+    /// The deepest nesting of conditionals, loops and the like.
+    #[serde(default)]
+    pub max_nesting: usize,
+}
+
+/// Directory totals are built by adding up their children; `max_nesting` is
+/// the deepest of theirs.
+impl AddAssign<&Metrics> for Metrics {
+    fn add_assign(&mut self, rhs: &Metrics) {
+        self.loc += rhs.loc;
+        self.complexity += rhs.complexity;
+        self.functions += rhs.functions;
+        self.lines += rhs.lines;
+        self.code += rhs.code;
+        self.comments += rhs.comments;
+        self.blanks += rhs.blanks;
+        self.cognitive += rhs.cognitive;
+        self.max_nesting = self.max_nesting.max(rhs.max_nesting);
+    }
+}
+
+/// One entry of the `ryoiki.cc.json` tree: a file, or a directory whose
+/// metrics are the sum of its children. With [`crate::ScanDepth::Functions`]
+/// files hold a node per function; their metrics stay those of the whole
+/// file.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Node {
+    pub name: String,
+    pub path: String,
+    pub kind: String,
+    pub metrics: Metrics,
+    pub language: Option<String>,
+    pub children: Option<Vec<Node>>,
+    /// Extra numeric metrics carried over from imported analyses (SonarQube,
+    /// ccsh, ...), keyed by their CodeCharta attribute name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub attributes: BTreeMap<String, f64>,
+    /// Change history from git; `None` outside a repository.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<GitMetrics>,
+    /// Normalized complexity times normalized commit count, in `0..=1`; a
+    /// directory has the highest score below it. `None` without git history.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hotspot_score: Option<f64>,
+    /// Files that tend to change together. Only the root carries edges.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub edges: Vec<CouplingEdge>,
+    /// Set on `function` nodes only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function: Option<FunctionSpan>,
+    /// Halstead measures and maintainability index of a file, or of all the
+    /// files below a directory. `None` for files ryoiki computes no
+    /// complexity for, such as Markdown or JSON, and for function nodes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub maintainability: Option<Maintainability>,
+}
+
+impl Node {
+    /// A file node at `path`, named after its last segment.
+    pub fn file(path: &str, metrics: Metrics) -> Node {
+        Node {
+            name: path.rsplit('/').next().unwrap_or(path).to_string(),
+            path: path.to_string(),
+            kind: "file".to_string(),
+            metrics,
+            language: None,
+            children: None,
+            attributes: BTreeMap::new(),
+            git: None,
+            hotspot_score: None,
+            edges: Vec::new(),
+            function: None,
+            maintainability: None,
+        }
+    }
+
+    /// A directory node at `path` whose metrics are the sum of `children`.
+    pub fn directory(path: &str, children: Vec<Node>) -> Node {
+        let mut metrics = Metrics::default();
+        for c in &children {
+            metrics += &c.metrics;
+        }
+        Node {
+            kind: "directory".to_string(),
+            children: Some(children),
+            ..Node::file(path, metrics)
+        }
+    }
+
+    /// The children of a directory; `None` for a file, even one with
+    /// function nodes below it.
+    pub fn entries(&self) -> Option<&[Node]> {
+        self.children
+            .as_deref()
+            .filter(|_| self.kind == "directory")
+    }
+
+    pub fn entries_mut(&mut self) -> Option<&mut Vec<Node>> {
+        self.children.as_mut().filter(|_| self.kind == "directory")
+    }
+}
+
+/// Where a function sits in its file. Its [`Node::metrics`] count the lines
+/// of the span as `loc`, its cyclomatic and cognitive complexity and its
+/// nesting.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FunctionSpan {
+    /// 1-based line of the name.
+    pub line: usize,
+    pub end_line: usize,
+    /// Declared parameters, without `self` / `this`.
+    pub params: usize,
+}
+
+/// Halstead measures and the maintainability index, of one file or combined
+/// over several.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Maintainability {
+    /// Halstead volume: operators and operands used, times the log2 of the
+    /// distinct ones.
+    pub volume: f64,
+    /// Halstead difficulty: half the distinct operators times how often each
+    /// distinct operand is used on average.
+    pub difficulty: f64,
+    /// Volume times difficulty.
+    pub effort: f64,
+    /// Maintainability index from 0 to 100; higher is easier to maintain.
+    pub index: f64,
+}
+
+impl Maintainability {
+    /// Combines files, each given with its code lines. Volume and effort add
+    /// up, difficulty is the combined effort per volume and the index is the
+    /// mean weighted by code lines. `None` without any files.
+    pub fn combine<'a>(
+        files: impl IntoIterator<Item = (&'a Maintainability, usize)>,
+    ) -> Option<Maintainability> {
+        let mut combined = Maintainability::default();
+        let mut count = 0usize;
+        let mut code = 0usize;
+        let mut weighted = 0.0;
+        let mut plain = 0.0;
+        for (m, lines) in files {
+            count += 1;
+            code += lines;
+            combined.volume += m.volume;
+            combined.effort += m.effort;
+            weighted += m.index * lines as f64;
+            plain += m.index;
+        }
+        if count == 0 {
+            return None;
+        }
+        if combined.volume > 0.0 {
+            combined.difficulty = combined.effort / combined.volume;
+        }
+        combined.index = if code > 0 {
+            weighted / code as f64
+        } else {
+            plain / count as f64
+        };
+        Some(combined)
+    }
+
+    /// [`Maintainability::combine`] over all files below `nodes`, the
+    /// children of a directory.
+    pub fn below(nodes: &[Node]) -> Option<Maintainability> {
+        fn collect<'a>(nodes: &'a [Node], out: &mut Vec<(&'a Maintainability, usize)>) {
+            for node in nodes {
+                match node.entries() {
+                    Some(children) => collect(children, out),
+                    None => out.extend(
+                        node.maintainability
+                            .as_ref()
+                            .map(|m| (m, node.metrics.code)),
+                    ),
+                }
+            }
+        }
+        let mut files = Vec::new();
+        collect(nodes, &mut files);
+        Maintainability::combine(files)
+    }
+}
+
+/// Temporal coupling between two files, both given by [`Node::path`] with
+/// `from` sorting first.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CouplingEdge {
+    pub from: String,
+    pub to: String,
+    /// Commits that changed both files.
+    pub support: usize,
+    /// `support` divided by the commits of the less frequently changed file:
+    /// how often a change to it also touched the other one.
+    pub confidence: f64,
+    /// `support` as a percentage of the two files' average commit count.
+    pub degree: f64,
+}
+
+/// Activity within the configured history window. Directories count each
+/// commit and author once, however many of their files were touched.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct GitMetrics {
+    /// Non-merge commits that changed the node.
+    pub commits: usize,
+    pub lines_added: usize,
+    pub lines_deleted: usize,
+    /// Distinct author emails.
+    pub authors: usize,
+    /// Age in days of the oldest commit, measured from the `HEAD` commit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_commit: Option<u64>,
+    /// Age in days of the newest commit, measured from the `HEAD` commit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_commit: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Totals {
+    pub files: usize,
+    pub lines: usize,
+    pub code: usize,
+    pub comments: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LanguageTotals {
+    /// tokei's display name (`C++`).
+    pub name: String,
+    /// The palette id tree nodes use for this language (`cpp`).
+    #[serde(default)]
+    pub id: String,
+    pub files: usize,
+    pub lines: usize,
+    pub code: usize,
+    pub comments: usize,
+    pub blanks: usize,
+    /// [`Maintainability::combine`] over the files of the language.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub maintainability: Option<Maintainability>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CategoryTotals {
+    pub name: String,
+    pub files: usize,
+    pub lines: usize,
+    pub code: usize,
+    pub comments: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AuditCount {
+    pub token: String,
+    pub count: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct AbcMetrics {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub magnitude: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct HalsteadMetrics {
+    pub n1_ops_unique: usize,
+    pub n2_operands_unique: usize,
+    pub ops_total: usize,
+    pub operands_total: usize,
+    pub volume: f64,
+    pub difficulty: f64,
+    pub effort: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct AdvancedMetrics {
+    pub comment_density_pct: f64,
+    pub cyclomatic_total: usize,
+    pub cyclomatic_density: f64,
+    pub abc: AbcMetrics,
+    /// Operators and operands pooled over every file that has a
+    /// [`Node::maintainability`], whatever its language.
+    pub halstead: HalsteadMetrics,
+    /// The root's [`Maintainability::index`]: all such files, weighted by
+    /// code lines.
+    pub maintainability_index: f64,
+    #[serde(default)]
+    pub cognitive: CognitiveMetrics,
+    /// Rust functions, methods, traits and impls in the files that parse.
+    #[serde(default)]
+    pub items: RustItems,
+}
+
+/// Cognitive complexity and nesting across all files.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CognitiveMetrics {
+    pub total: usize,
+    /// Mean over the functions syntax trees found.
+    pub per_function: f64,
+    /// The highest of any function.
+    pub max_function: usize,
+    /// That function, as `<file path>#<name>`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_function_path: Option<String>,
+    /// The deepest nesting in any file.
+    pub max_nesting: usize,
+}
+
+/// A file the scanner skipped or could only partially analyse.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Diagnostic {
+    pub path: String,
+    pub message: String,
+}
+
+/// Everything a single [`crate::run_scan`] produced.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ScanReport {
+    pub tree: Node,
+    pub totals: Totals,
+    pub languages: Vec<LanguageTotals>,
+    pub categories: Vec<CategoryTotals>,
+    pub audit: Vec<AuditCount>,
+    pub advanced: AdvancedMetrics,
+    pub diagnostics: Vec<Diagnostic>,
+    /// Scan cache hits and misses; `None` when the scan ran without a cache.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache: Option<CacheStats>,
+    /// The highest-scoring files, best first. Empty without git history.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hotspots: Vec<Hotspot>,
+    /// The commit [`crate::ScanConfig::revision`] resolved to or, for
+    /// working-tree scans, `HEAD` (uncommitted changes included). `None`
+    /// outside a git repository.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<ScanCommit>,
+    /// The summary metrics of earlier revisions, oldest first, when the
+    /// report comes from [`crate::run_timeline`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub timeline: Vec<TimelinePoint>,
+}
+
+/// The summary metrics of one revision in a timeline.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TimelinePoint {
+    pub commit: ScanCommit,
+    pub totals: Totals,
+    pub advanced: AdvancedMetrics,
+}
+
+/// The commit a scan describes.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ScanCommit {
+    pub id: String,
+    /// Commit time in seconds since the epoch.
+    pub time: i64,
+}
+
+/// A file that is both complex and frequently changed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Hotspot {
+    pub path: String,
+    pub score: f64,
+    pub complexity: usize,
+    pub commits: usize,
+}
+
+/// Files whose analysis was reused from, or recomputed despite, the cache.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct CacheStats {
+    pub hits: usize,
+    pub misses: usize,
+}
+
+/// The shape of `ryoiki.metrics.json`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MetricsSummary {
+    pub totals: Totals,
+    pub advanced: AdvancedMetrics,
+    /// Line totals and maintainability per language.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub languages: Vec<LanguageTotals>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hotspots: Vec<Hotspot>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub timeline: Vec<TimelinePoint>,
+}
+
+impl ScanReport {
+    pub fn summary(&self) -> MetricsSummary {
+        MetricsSummary {
+            totals: self.totals.clone(),
+            advanced: self.advanced.clone(),
+            languages: self.languages.clone(),
+            hotspots: self.hotspots.clone(),
+            timeline: self.timeline.clone(),
+        }
+    }
+}
+
+/// Writes `ryoiki.cc.json` and `ryoiki.metrics.json` into every configured
+/// output directory, creating it when missing. The metrics directory also
+/// gets `ryoiki.codecharta.cc.json` for CodeCharta itself.
+pub fn write_outputs(report: &ScanReport, outputs: &ScanOutputs) -> io::Result<()> {
+    let json = serde_json::to_string_pretty(&report.tree)?;
+    let metrics_json = serde_json::to_string_pretty(&report.summary())?;
+    for dir in [&outputs.metrics_dir, &outputs.web_dir]
+        .into_iter()
+        .flatten()
+    {
+        fs::create_dir_all(dir)?;
+        fs::write(dir.join("ryoiki.cc.json"), &json)?;
+        fs::write(dir.join("ryoiki.metrics.json"), &metrics_json)?;
+    }
+    if let Some(dir) = &outputs.metrics_dir {
+        let cc = to_codecharta(&report.tree, &report.tree.name);
+        fs::write(
+            dir.join("ryoiki.codecharta.cc.json"),
+            serde_json::to_string_pretty(&cc)?,
+        )?;
+    }
+    Ok(())
+}
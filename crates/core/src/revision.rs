@@ -0,0 +1,217 @@
+//! Scanning a git revision straight from the object database.
+//!
+//! [`walk_revision`] is the counterpart of the working-tree walk in
+//! [`crate::scan`]: it lists the tree of a commit below the scan root,
+//! applies the same include/exclude globs, hidden-file and size filters,
+//! the `.gitignore` files committed alongside and the repository's and
+//! user's exclude files, and analyzes blob contents instead of files on
+//! disk. The working tree and index are never touched.
+//!
+//! Symlinks and submodules are skipped; their targets are not part of the
+//! revision's tree.
+
+use crate::cache::ScanCache;
+use crate::config::ScanConfig;
+use crate::history::{resolve_commit, scan_commit, workdir_prefix};
+use crate::report::ScanCommit;
+use crate::scan::{ScanError, Walked, overrides};
+use ignore::Match;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::overrides::Override;
+use std::path::{Path, PathBuf};
+
+/// A file to analyze: its path relative to the scan root and its contents.
+struct Blob {
+    rel: PathBuf,
+    data: Vec<u8>,
+}
+
+/// Lists and analyzes the files below `cfg.root` as of `rev`, a commit id,
+/// tag or branch name. Returns the walk results and the commit `rev`
+/// resolved to.
+pub(crate) fn walk_revision(
+    cfg: &ScanConfig,
+    rev: &str,
+    cache: &ScanCache,
+) -> Result<(Vec<Walked>, ScanCommit), ScanError> {
+    let err = |e: &dyn std::fmt::Display| ScanError::Revision(rev.to_string(), e.to_string());
+    let repo = gix::discover(&cfg.root).map_err(|e| err(&e))?;
+    let commit = resolve_commit(&repo, rev).map_err(|e| err(&e))?;
+    let scanned = scan_commit(&commit).map_err(|e| err(&e))?;
+    let prefix = workdir_prefix(&repo, &cfg.root)
+        .map_err(|e| err(&e))?
+        .ok_or_else(|| err(&"the scan root is not inside a git work tree"))?;
+    let mut tree = commit.tree().map_err(|e| err(&e))?;
+    if !prefix.is_empty() {
+        let dir = prefix.trim_end_matches('/');
+        tree = match tree.lookup_entry_by_path(dir).map_err(|e| err(&e))? {
+            Some(entry) if entry.mode().is_tree() => {
+                entry.object().map_err(|e| err(&e))?.into_tree()
+            }
+            _ => return Err(err(&format!("{} does not exist in this revision", dir))),
+        };
+    }
+
+    let filter = Filter {
+        cfg,
+        overrides: overrides(cfg)?,
+        outer: outer_ignores(&repo),
+        prefix: PathBuf::from(&prefix),
+    };
+    let mut walked = Vec::new();
+    let mut blobs = Vec::new();
+    collect(
+        &tree,
+        Path::new(""),
+        &filter,
+        &mut Vec::new(),
+        &mut walked,
+        &mut blobs,
+    )
+    .map_err(|e| err(&e))?;
+    walked.extend(analyze(blobs, cache));
+    Ok((walked, scanned))
+}
+
+/// The filters the working-tree walker applies, for paths relative to the
+/// scan root.
+struct Filter<'a> {
+    cfg: &'a ScanConfig,
+    overrides: Override,
+    /// The ignore files kept outside the tree, innermost first; see
+    /// [`outer_ignores`].
+    outer: Vec<Gitignore>,
+    /// The scan root relative to the work tree, where `outer` is rooted.
+    prefix: PathBuf,
+}
+
+/// The ignore files the working-tree walk applies that are not committed:
+/// `.git/info/exclude`, git's global excludes file and
+/// `~/.gitignore_global`, innermost first. They are rooted at the work tree
+/// and matched against paths relative to it.
+fn outer_ignores(repo: &gix::Repository) -> Vec<Gitignore> {
+    let mut exclude = GitignoreBuilder::new("");
+    exclude.add(repo.git_dir().join("info").join("exclude"));
+    let (global, _) = GitignoreBuilder::new("").build_global();
+    let mut home = GitignoreBuilder::new("");
+    if let Ok(dir) = std::env::var("HOME").or_else(|_| std::env::var("USERPROFILE")) {
+        home.add(Path::new(&dir).join(".gitignore_global"));
+    }
+    [exclude.build().ok(), Some(global), home.build().ok()]
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+impl Filter<'_> {
+    /// Whether `rel` is skipped. As in the walker, an include or exclude glob
+    /// decides first, then hidden files, then the innermost `.gitignore`
+    /// with an opinion and last the ignore files outside the tree.
+    fn skips(&self, rel: &Path, name: &str, is_dir: bool, ignores: &[Gitignore]) -> bool {
+        let abs = self.cfg.root.join(rel);
+        match self.overrides.matched(&abs, is_dir) {
+            Match::Ignore(_) => return true,
+            Match::Whitelist(_) => return false,
+            Match::None => {}
+        }
+        if !self.cfg.hidden && name.starts_with('.') {
+            return true;
+        }
+        let in_repo = self.prefix.join(rel);
+        ignores
+            .iter()
+            .rev()
+            .map(|gi| gi.matched(&abs, is_dir))
+            .chain(self.outer.iter().map(|gi| gi.matched(&in_repo, is_dir)))
+            .find(|m| !m.is_none())
+            .is_some_and(|m| m.is_ignore())
+    }
+}
+
+/// Records the directories below `tree` (at `rel`) in `walked` and the blobs
+/// to analyze in `blobs`. `ignores` holds the `.gitignore` files of `rel`
+/// and its ancestors, outermost first.
+fn collect(
+    tree: &gix::Tree<'_>,
+    rel: &Path,
+    filter: &Filter<'_>,
+    ignores: &mut Vec<Gitignore>,
+    walked: &mut Vec<Walked>,
+    blobs: &mut Vec<Blob>,
+) -> Result<(), String> {
+    let entries = tree
+        .iter()
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    let gitignore = entries
+        .iter()
+        .find(|e| e.filename() == ".gitignore" && e.mode().is_blob())
+        .map(|e| -> Result<Gitignore, String> {
+            let data = e.object().map_err(|e| e.to_string())?.detach().data;
+            let mut b = GitignoreBuilder::new(filter.cfg.root.join(rel));
+            for line in String::from_utf8_lossy(&data).lines() {
+                b.add_line(None, line).map_err(|e| e.to_string())?;
+            }
+            b.build().map_err(|e| e.to_string())
+        })
+        .transpose()?;
+    let pushed = gitignore.is_some();
+    ignores.extend(gitignore);
+
+    for entry in &entries {
+        let name = entry.filename().to_string();
+        let path = rel.join(&name);
+        let mode = entry.mode();
+        if !(mode.is_tree() || mode.is_blob())
+            || filter.skips(&path, &name, mode.is_tree(), ignores)
+        {
+            continue;
+        }
+        let object = entry.object().map_err(|e| e.to_string())?;
+        if mode.is_tree() {
+            collect(&object.into_tree(), &path, filter, ignores, walked, blobs)?;
+            walked.push(Walked::Dir(path));
+        } else if filter
+            .cfg
+            .max_file_size
+            .is_none_or(|max| object.data.len() as u64 <= max)
+        {
+            blobs.push(Blob {
+                rel: path,
+                data: object.detach().data,
+            });
+        }
+    }
+    if pushed {
+        ignores.pop();
+    }
+    Ok(())
+}
+
+/// Analyzes `blobs` on all cores.
+fn analyze(blobs: Vec<Blob>, cache: &ScanCache) -> Vec<Walked> {
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk = blobs.len().div_ceil(threads).max(1);
+    std::thread::scope(|s| {
+        let workers: Vec<_> = blobs
+            .chunks(chunk)
+            .map(|chunk| {
+                s.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|b| {
+                            let result = cache
+                                .analyze_blob(&b.rel.to_string_lossy(), &b.data)
+                                .map(Box::new);
+                            Walked::File(b.rel.clone(), result)
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|w| w.join().unwrap_or_else(|e| std::panic::resume_unwind(e)))
+            .collect()
+    })
+}
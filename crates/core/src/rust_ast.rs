@@ -0,0 +1,407 @@
+//! Rust analysis on the syntax tree from `syn`.
+//!
+//! [`analyze`] counts decision points, cognitive complexity and nesting per
+//! function, and the items of a file.
+//! Identifiers such as `diff` or `match_str` and `fn` in type positions no
+//! longer count, as they did with the text heuristics in
+//! [`crate::metrics_calc`]; those remain the fallback for files `syn` cannot
+//! parse.
+
+use crate::analysis::{FunctionAnalysis, RustItems, SyntaxAnalysis};
+use syn::punctuated::Punctuated;
+use syn::visit::{self, Visit};
+use syn::{Expr, Token};
+
+/// Parses `txt` as a Rust file; `None` when it does not parse.
+pub(crate) fn analyze(txt: &str) -> Option<(SyntaxAnalysis, RustItems)> {
+    let file = syn::parse_file(txt).ok()?;
+    let mut v = Visitor::default();
+    v.visit_file(&file);
+    Some((v.syntax, v.items))
+}
+
+#[derive(Default)]
+struct Visitor {
+    syntax: SyntaxAnalysis,
+    items: RustItems,
+    /// Indices into `syntax.functions` of the functions being visited,
+    /// innermost last.
+    open: Vec<usize>,
+    /// Enclosing modules and `impl` / `trait` names.
+    scope: Vec<String>,
+    /// Nesting inside the innermost open function, or at file level.
+    depth: usize,
+    /// The operator of the `&&` / `||` run the expression being visited
+    /// continues, if any.
+    sequence: Option<&'static str>,
+}
+
+impl Visitor {
+    fn decide(&mut self, n: usize) {
+        self.syntax.decisions += n;
+        if let Some(&i) = self.open.last() {
+            self.syntax.functions[i].complexity += n;
+        }
+    }
+
+    fn cognitive(&mut self, n: usize) {
+        self.syntax.cognitive += n;
+        if let Some(&i) = self.open.last() {
+            self.syntax.functions[i].cognitive += n;
+        }
+    }
+
+    /// A conditional, loop or `match`: 1 plus the current nesting.
+    fn structure(&mut self) {
+        self.cognitive(1 + self.depth);
+    }
+
+    /// An `if` and its `else` chain; `else_if` when it is the `else` of
+    /// another.
+    fn if_chain(&mut self, node: &syn::ExprIf, else_if: bool) {
+        self.decide(1);
+        if else_if {
+            self.cognitive(1);
+        } else {
+            self.structure();
+        }
+        self.nested(|v| {
+            v.visit_expr(&node.cond);
+            v.visit_block(&node.then_branch);
+        });
+        match node.else_branch.as_ref().map(|(_, e)| &**e) {
+            Some(Expr::If(e)) => self.if_chain(e, true),
+            Some(e) => {
+                self.cognitive(1);
+                self.nested(|v| v.visit_expr(e));
+            }
+            None => {}
+        }
+    }
+
+    /// Visits a conditional, loop or closure body with `walk`, one level
+    /// deeper.
+    fn nested(&mut self, walk: impl FnOnce(&mut Self)) {
+        self.depth += 1;
+        self.syntax.max_nesting = self.syntax.max_nesting.max(self.depth);
+        if let Some(&i) = self.open.last() {
+            let f = &mut self.syntax.functions[i];
+            f.nesting = f.nesting.max(self.depth);
+        }
+        walk(self);
+        self.depth -= 1;
+    }
+
+    /// Records the function of `sig` and `body` and visits it with `walk`.
+    fn function(&mut self, sig: &syn::Signature, body: &syn::Block, walk: impl FnOnce(&mut Self)) {
+        let ident = &sig.ident;
+        let name = self
+            .scope
+            .iter()
+            .map(String::as_str)
+            .chain([ident.to_string().as_str()])
+            .collect::<Vec<_>>()
+            .join("::");
+        self.open.push(self.syntax.functions.len());
+        self.syntax.functions.push(FunctionAnalysis {
+            name,
+            line: ident.span().start().line,
+            end_line: body.brace_token.span.close().end().line,
+            // `self` is not counted, as it is implicit in other languages.
+            params: sig
+                .inputs
+                .iter()
+                .filter(|a| matches!(a, syn::FnArg::Typed(_)))
+                .count(),
+            complexity: 1,
+            cognitive: 0,
+            nesting: 0,
+        });
+        // A nested function is not part of the scope or nesting of what it
+        // contains.
+        let scope = std::mem::take(&mut self.scope);
+        let depth = std::mem::take(&mut self.depth);
+        walk(self);
+        self.scope = scope;
+        self.depth = depth;
+        self.open.pop();
+    }
+
+    fn scoped(&mut self, name: String, walk: impl FnOnce(&mut Self)) {
+        self.scope.push(name);
+        walk(self);
+        self.scope.pop();
+    }
+}
+
+/// The name of the type an `impl` is for: the last path segment, or `impl`
+/// for references, tuples and the like.
+fn type_name(ty: &syn::Type) -> String {
+    match ty {
+        syn::Type::Path(p) => p
+            .path
+            .segments
+            .last()
+            .map_or_else(|| "impl".to_string(), |s| s.ident.to_string()),
+        _ => "impl".to_string(),
+    }
+}
+
+fn logical(op: &syn::BinOp) -> Option<&'static str> {
+    match op {
+        syn::BinOp::And(_) => Some("&&"),
+        syn::BinOp::Or(_) => Some("||"),
+        _ => None,
+    }
+}
+
+impl<'ast> Visit<'ast> for Visitor {
+    fn visit_item_fn(&mut self, node: &'ast syn::ItemFn) {
+        self.items.functions += 1;
+        self.function(&node.sig, &node.block, |v| visit::visit_item_fn(v, node));
+    }
+
+    fn visit_impl_item_fn(&mut self, node: &'ast syn::ImplItemFn) {
+        self.items.methods += 1;
+        self.function(&node.sig, &node.block, |v| {
+            visit::visit_impl_item_fn(v, node)
+        });
+    }
+
+    fn visit_trait_item_fn(&mut self, node: &'ast syn::TraitItemFn) {
+        self.items.methods += 1;
+        if let Some(body) = &node.default {
+            self.function(&node.sig, body, |v| visit::visit_trait_item_fn(v, node));
+        } else {
+            visit::visit_trait_item_fn(self, node);
+        }
+    }
+
+    fn visit_item_impl(&mut self, node: &'ast syn::ItemImpl) {
+        self.items.impls += 1;
+        self.scoped(type_name(&node.self_ty), |v| {
+            visit::visit_item_impl(v, node)
+        });
+    }
+
+    fn visit_item_trait(&mut self, node: &'ast syn::ItemTrait) {
+        self.items.traits += 1;
+        self.scoped(node.ident.to_string(), |v| visit::visit_item_trait(v, node));
+    }
+
+    fn visit_item_mod(&mut self, node: &'ast syn::ItemMod) {
+        self.scoped(node.ident.to_string(), |v| visit::visit_item_mod(v, node));
+    }
+
+    fn visit_expr_if(&mut self, node: &'ast syn::ExprIf) {
+        self.if_chain(node, false);
+    }
+
+    fn visit_expr_while(&mut self, node: &'ast syn::ExprWhile) {
+        self.decide(1);
+        self.structure();
+        self.nested(|v| visit::visit_expr_while(v, node));
+    }
+
+    fn visit_expr_for_loop(&mut self, node: &'ast syn::ExprForLoop) {
+        self.decide(1);
+        self.structure();
+        self.nested(|v| visit::visit_expr_for_loop(v, node));
+    }
+
+    fn visit_expr_loop(&mut self, node: &'ast syn::ExprLoop) {
+        self.structure();
+        self.nested(|v| visit::visit_expr_loop(v, node));
+    }
+
+    fn visit_expr_match(&mut self, node: &'ast syn::ExprMatch) {
+        let guards = node.arms.iter().filter(|a| a.guard.is_some()).count();
+        self.decide(node.arms.len().saturating_sub(1) + guards);
+        self.structure();
+        self.nested(|v| visit::visit_expr_match(v, node));
+    }
+
+    fn visit_expr_try(&mut self, node: &'ast syn::ExprTry) {
+        self.decide(1);
+        visit::visit_expr_try(self, node);
+    }
+
+    fn visit_expr_binary(&mut self, node: &'ast syn::ExprBinary) {
+        let op = logical(&node.op);
+        if op.is_some() {
+            self.decide(1);
+            if self.sequence != op {
+                self.cognitive(1);
+            }
+        }
+        for side in [&*node.left, &*node.right] {
+            self.sequence = match side {
+                Expr::Binary(b) if op.is_some() && logical(&b.op) == op => op,
+                _ => None,
+            };
+            self.visit_expr(side);
+        }
+        self.sequence = None;
+    }
+
+    fn visit_expr_break(&mut self, node: &'ast syn::ExprBreak) {
+        if node.label.is_some() {
+            self.cognitive(1);
+        }
+        visit::visit_expr_break(self, node);
+    }
+
+    fn visit_expr_continue(&mut self, node: &'ast syn::ExprContinue) {
+        if node.label.is_some() {
+            self.cognitive(1);
+        }
+        visit::visit_expr_continue(self, node);
+    }
+
+    fn visit_expr_closure(&mut self, node: &'ast syn::ExprClosure) {
+        self.decide(1);
+        self.nested(|v| visit::visit_expr_closure(v, node));
+    }
+
+    fn visit_local(&mut self, node: &'ast syn::Local) {
+        if node.init.as_ref().is_some_and(|i| i.diverge.is_some()) {
+            self.decide(1);
+            self.structure();
+        }
+        visit::visit_local(self, node);
+    }
+
+    /// Macro arguments are plain tokens to `syn`; the common comma-separated
+    /// ones (`vec!`, `assert!`, `format!`, ...) are visited as expressions.
+    fn visit_macro(&mut self, node: &'ast syn::Macro) {
+        if let Ok(args) = node.parse_body_with(Punctuated::<Expr, Token![,]>::parse_terminated) {
+            for arg in &args {
+                self.visit_expr(arg);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn complexity(syntax: &SyntaxAnalysis) -> Vec<(&str, usize, usize)> {
+        syntax
+            .functions
+            .iter()
+            .map(|f| (f.name.as_str(), f.complexity, f.nesting))
+            .collect()
+    }
+
+    #[test]
+    fn test_identifiers_and_fn_types_do_not_count() {
+        let src = "fn diff(modified: bool, match_str: &str, if_let_chain: fn(u8) -> u8) -> u8 {\n    \
+                   let or_else = modified;\n    if_let_chain(match_str.len() as u8)\n}\n";
+        let (syntax, _) = analyze(src).expect("parses");
+        assert_eq!(syntax.decisions, 0);
+        assert_eq!(complexity(&syntax), [("diff", 1, 0)]);
+    }
+
+    #[test]
+    fn test_function_complexity_counts_rust_decisions() {
+        let src = r#"
+mod m {
+    pub struct S;
+    pub trait T {
+        fn required(&self);
+        fn provided(&self) -> u8 { if true { 1 } else { 0 } }
+    }
+    impl S {
+        fn run(&self, x: Option<u8>) -> Result<u8, ()> {
+            let Some(y) = x else { return Err(()) };
+            let v = match y {
+                0 => 1,
+                n if n > 5 && n < 9 => 2,
+                _ => 3,
+            };
+            for _ in 0..v { while false {} }
+            let f = |a: u8| a.checked_add(1).ok_or(());
+            assert!(f(v)? > 0 || v == 0);
+            fn helper() {}
+            Ok(v)
+        }
+    }
+    fn chain(x: u8) -> u8 { if x == 0 { 0 } else if x == 1 { 1 } else { 2 } }
+}
+"#;
+        let (syntax, items) = analyze(src).expect("parses");
+        // let-else, 2 extra arms, 1 guard, &&, for, while, closure, ?, ||.
+        assert_eq!(
+            complexity(&syntax),
+            [
+                ("m::T::provided", 2, 1),
+                ("m::S::run", 11, 2),
+                ("helper", 1, 0),
+                ("m::chain", 3, 1),
+            ]
+        );
+        assert_eq!(syntax.decisions, 13);
+        assert_eq!(syntax.max_nesting, 2);
+        let run = &syntax.functions[1];
+        assert_eq!((run.line, run.end_line, run.params), (9, 21, 1));
+        assert_eq!(
+            items,
+            RustItems {
+                functions: 2,
+                methods: 3,
+                traits: 1,
+                impls: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_cognitive_complexity_follows_sonar_rules() {
+        let src = r#"
+fn sum_of_primes(max: u32) -> u32 {
+    let mut total = 0;
+    'outer: for i in 1..=max {
+        for j in 2..i {
+            if i % j == 0 {
+                continue 'outer;
+            }
+        }
+        total += i;
+    }
+    total
+}
+fn words(n: u8) -> &'static str {
+    match n { 1 => "one", 2 => "a couple", _ => "lots" }
+}
+fn mixed(a: bool, b: bool, c: bool) -> u8 {
+    let f = |x: bool| if x { 1 } else { 0 };
+    if a && b && c || a { f(b) } else if !(a || b) { 2 } else { 3 }
+}
+"#;
+        let (syntax, _) = analyze(src).expect("parses");
+        let cognitive: Vec<_> = syntax
+            .functions
+            .iter()
+            .map(|f| (f.name.as_str(), f.cognitive))
+            .collect();
+        // SonarSource's own example scores 7. `mixed`: the closure's `if`
+        // at nesting 1 and its `else` (3), `if` with two operator runs (3),
+        // `else if` with one (2) and `else` (1).
+        assert_eq!(
+            cognitive,
+            [("sum_of_primes", 7), ("words", 1), ("mixed", 9)]
+        );
+        assert_eq!(syntax.cognitive, 17);
+    }
+
+    #[test]
+    fn test_unparsable_source_falls_back_to_heuristics() {
+        assert!(analyze("fn broken( {").is_none());
+        let a = crate::analysis::analyze_source(Some("rust".to_string()), "fn broken( { if x {");
+        assert!(a.syntax.is_none());
+        assert!(a.rust.is_some());
+        assert_eq!(a.metrics.functions, 1);
+        assert_eq!(a.metrics.complexity, 2);
+    }
+}
@@ -0,0 +1,689 @@
+use crate::analysis::{
+    AUDIT_TOKENS, FileAnalysis, LineStats, RustItems, SyntaxAnalysis, analyze_source,
+};
+use crate::cache::{CachedAnalysis, ScanCache};
+use crate::config::{ScanConfig, ScanDepth};
+use crate::coupling::coupling_edges;
+use crate::history::{annotate, head_commit, read_history};
+use crate::hotspots::score_hotspots;
+use crate::language::{dominant_language, language_from_path, language_id};
+use crate::report::{
+    AbcMetrics, AdvancedMetrics, AuditCount, CategoryTotals, CognitiveMetrics, Diagnostic,
+    FunctionSpan, HalsteadMetrics, LanguageTotals, Maintainability, Metrics, Node, ScanReport,
+    Totals,
+};
+use crate::revision::walk_revision;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::overrides::{Override, OverrideBuilder};
+use ignore::{WalkBuilder, WalkState};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokei::{Config, LanguageType};
+
+#[derive(Debug)]
+pub enum ScanError {
+    RootNotFound(PathBuf),
+    InvalidGlob(String),
+    /// The revision (as given) could not be read from the repository.
+    Revision(String, String),
+}
+
+impl fmt::Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScanError::RootNotFound(p) => {
+                write!(f, "scan root {} is not a directory", p.display())
+            }
+            ScanError::InvalidGlob(e) => write!(f, "invalid include/exclude glob: {}", e),
+            ScanError::Revision(rev, e) => write!(f, "cannot scan revision {}: {}", rev, e),
+        }
+    }
+}
+
+impl std::error::Error for ScanError {}
+
+/// Scans `cfg.root` and returns the tree, totals and advanced metrics.
+///
+/// The tree is walked once, in parallel; each file is read and analyzed by a
+/// single worker. Only source files (and `.gitignore` files) are read. The one
+/// exception is [`ScanConfig::cache_path`]: when set, the per-file cache is
+/// loaded from and saved back to it. With [`ScanConfig::history`] set, the
+/// surrounding git repository is read as well.
+///
+/// With [`ScanConfig::revision`] set, the files come from that commit in the
+/// git object database instead of the working tree, and the history ends at
+/// it.
+pub fn run_scan(cfg: &ScanConfig) -> Result<ScanReport, ScanError> {
+    let mut cache = match &cfg.cache_path {
+        Some(path) => ScanCache::load(path, cfg),
+        None => ScanCache::new(cfg),
+    };
+    let mut report = scan_with_cache(cfg, &mut cache)?;
+    if let Some(path) = &cfg.cache_path
+        && let Err(e) = cache.save(path)
+    {
+        report.diagnostics.push(Diagnostic {
+            path: path.to_string_lossy().to_string(),
+            message: format!("cache not saved: {}", e),
+        });
+    }
+    Ok(report)
+}
+
+/// [`run_scan`] with a cache the caller loads and saves.
+pub(crate) fn scan_with_cache(
+    cfg: &ScanConfig,
+    cache: &mut ScanCache,
+) -> Result<ScanReport, ScanError> {
+    let scan_root = cfg.root.as_path();
+    if !scan_root.is_dir() {
+        return Err(ScanError::RootNotFound(scan_root.to_path_buf()));
+    }
+    let revision = cfg.revision.as_deref();
+    // Reading git history is independent of the walk, so it runs alongside.
+    let (walked, history) = std::thread::scope(|s| {
+        let history = cfg
+            .history
+            .as_ref()
+            .map(|opts| s.spawn(move || read_history(scan_root, revision, opts)));
+        let walked = match revision {
+            Some(rev) => walk_revision(cfg, rev, cache).map(|(w, c)| (w, Some(c))),
+            None => {
+                walk(cfg, root_gitignore(scan_root), cache).map(|w| (w, head_commit(scan_root)))
+            }
+        };
+        let history = history.map(|h| h.join().unwrap_or_else(|e| std::panic::resume_unwind(e)));
+        (walked, history)
+    });
+    let (walked, commit) = walked?;
+
+    let mut files: BTreeMap<PathBuf, FileAnalysis> = BTreeMap::new();
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    for w in walked {
+        match w {
+            Walked::Dir(rel) => dirs.push(rel),
+            Walked::File(rel, Ok(c)) => {
+                files.insert(rel.clone(), c.analysis().clone());
+                cache.record(&rel.to_string_lossy(), *c);
+            }
+            Walked::File(rel, Err(e)) => diagnostics.push(Diagnostic {
+                path: rel.to_string_lossy().to_string(),
+                message: e.to_string(),
+            }),
+        }
+    }
+    diagnostics.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let mut totals = Totals::default();
+    let mut by_language: BTreeMap<String, LanguageTotals> = BTreeMap::new();
+    for lines in files.values().filter_map(|f| f.lines.as_ref()) {
+        let e = by_language
+            .entry(lines.language.clone())
+            .or_insert_with(|| LanguageTotals {
+                name: lines.language.clone(),
+                id: language_id(&lines.language),
+                files: 0,
+                lines: 0,
+                code: 0,
+                comments: 0,
+                blanks: 0,
+                maintainability: None,
+            });
+        e.files += 1;
+        e.lines += lines.code + lines.comments + lines.blanks;
+        e.code += lines.code;
+        e.comments += lines.comments;
+        e.blanks += lines.blanks;
+    }
+    let mut by_language_maintainability: BTreeMap<&str, Vec<(&Maintainability, usize)>> =
+        BTreeMap::new();
+    for f in files.values() {
+        if let (Some(lines), Some(m)) = (&f.lines, &f.maintainability) {
+            by_language_maintainability
+                .entry(lines.language.as_str())
+                .or_default()
+                .push((m, lines.code));
+        }
+    }
+    for (name, parts) in by_language_maintainability {
+        if let Some(e) = by_language.get_mut(name) {
+            e.maintainability = Maintainability::combine(parts);
+        }
+    }
+    let mut language_totals: Vec<LanguageTotals> = by_language.into_values().collect();
+    language_totals.sort_by_key(|l| std::cmp::Reverse(l.code));
+
+    let mut rust_lines: usize = 0;
+    let mut rust_code: usize = 0;
+    let mut rust_comments: usize = 0;
+    let mut cat_totals: HashMap<String, CategoryTotals> = HashMap::new();
+
+    for language in &language_totals {
+        totals.lines += language.lines;
+        totals.code += language.code;
+        totals.comments += language.comments;
+        totals.files += language.files;
+
+        if language.name == "Rust" {
+            rust_lines = language.lines;
+            rust_code = language.code;
+            rust_comments = language.comments;
+        }
+        let cat = classify(&language.name);
+        let e = cat_totals
+            .entry(cat.to_string())
+            .or_insert_with(|| CategoryTotals {
+                name: cat.to_string(),
+                files: 0,
+                lines: 0,
+                code: 0,
+                comments: 0,
+            });
+        e.files += language.files;
+        e.lines += language.lines;
+        e.code += language.code;
+        e.comments += language.comments;
+    }
+    let mut categories: Vec<CategoryTotals> = cat_totals.into_values().collect();
+    categories.sort_by(|a, b| b.code.cmp(&a.code).then_with(|| a.name.cmp(&b.name)));
+
+    let mut audit_counts: HashMap<&str, usize> = AUDIT_TOKENS.iter().map(|t| (*t, 0)).collect();
+    let mut abc_a: usize = 0;
+    let mut abc_b: usize = 0;
+    let mut abc_c: usize = 0;
+    let mut cc_decisions: usize = 0;
+    let mut halstead_ops_unique: HashSet<&str> = HashSet::new();
+    let mut halstead_operands_unique: HashSet<&str> = HashSet::new();
+    let mut halstead_ops_total: usize = 0;
+    let mut halstead_operands_total: usize = 0;
+    let mut items = RustItems::default();
+
+    for r in files.values().filter_map(|f| f.rust.as_ref()) {
+        for (t, c) in &r.audit {
+            if let Some(total) = audit_counts.get_mut(t.as_str()) {
+                *total += c;
+            }
+        }
+        abc_a += r.abc_a;
+        abc_b += r.abc_b;
+        abc_c += r.abc_c;
+        cc_decisions += r.cc_decisions;
+        items += &r.items;
+    }
+    // Halstead measures cover every language with an operator set.
+    for h in files.values().filter_map(|f| f.halstead.as_ref()) {
+        halstead_ops_total += h.ops_total;
+        halstead_operands_total += h.operands_total;
+        halstead_ops_unique.extend(h.ops.iter().map(|s| s.as_str()));
+        halstead_operands_unique.extend(h.operands.iter().map(|s| s.as_str()));
+    }
+
+    let cc_total = cc_decisions + 1;
+    let cc_density = if rust_code > 0 {
+        (cc_total as f64) / (rust_code as f64)
+    } else {
+        0.0
+    };
+    let abc_mag = ((abc_a * abc_a + abc_b * abc_b + abc_c * abc_c) as f64).sqrt();
+    let n1 = halstead_ops_unique.len();
+    let n2 = halstead_operands_unique.len();
+    let n = n1 + n2;
+    let n_big = halstead_ops_total + halstead_operands_total;
+    let h_volume = if n > 0 {
+        (n_big as f64) * ((n as f64).log2())
+    } else {
+        0.0
+    };
+    let h_difficulty = if n2 > 0 {
+        (n1 as f64 / 2.0) * (halstead_operands_total as f64 / n2 as f64)
+    } else {
+        0.0
+    };
+    let h_effort = h_difficulty * h_volume;
+    let comment_density = if rust_lines > 0 {
+        (rust_comments as f64) * 100.0 / (rust_lines as f64)
+    } else {
+        0.0
+    };
+
+    let mut audit: Vec<AuditCount> = audit_counts
+        .into_iter()
+        .map(|(token, count)| AuditCount {
+            token: token.to_string(),
+            count,
+        })
+        .collect();
+    audit.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.token.cmp(&b.token)));
+
+    let mut tree = assemble_tree(scan_root, &dirs, &files, cfg.depth);
+    match history {
+        Some(Ok(Some(history))) => {
+            annotate(&mut tree, &history);
+            if let Some(opts) = &cfg.coupling {
+                let paths: HashMap<String, String> = files
+                    .keys()
+                    .map(|p| {
+                        let node_path = p.to_string_lossy().to_string();
+                        (node_path.replace('\\', "/"), node_path)
+                    })
+                    .collect();
+                tree.edges = coupling_edges(&history, &paths, opts);
+            }
+        }
+        Some(Err(e)) => diagnostics.push(Diagnostic {
+            path: ".".to_string(),
+            message: e.to_string(),
+        }),
+        Some(Ok(None)) | None => {}
+    }
+    let hotspots = score_hotspots(&mut tree, cfg.hotspot_top);
+    let mi = tree.maintainability.as_ref().map_or(0.0, |m| m.index);
+    let cache_stats = cfg.cache_path.is_some().then(|| cache.stats().clone());
+
+    Ok(ScanReport {
+        tree,
+        totals,
+        languages: language_totals,
+        categories,
+        audit,
+        advanced: AdvancedMetrics {
+            comment_density_pct: comment_density,
+            cyclomatic_total: cc_total,
+            cyclomatic_density: cc_density,
+            abc: AbcMetrics {
+                a: abc_a as f64,
+                b: abc_b as f64,
+                c: abc_c as f64,
+                magnitude: abc_mag,
+            },
+            halstead: HalsteadMetrics {
+                n1_ops_unique: n1,
+                n2_operands_unique: n2,
+                ops_total: halstead_ops_total,
+                operands_total: halstead_operands_total,
+                volume: h_volume,
+                difficulty: h_difficulty,
+                effort: h_effort,
+            },
+            maintainability_index: mi,
+            cognitive: cognitive_summary(&files),
+            items,
+        },
+        diagnostics,
+        cache: cache_stats,
+        hotspots,
+        commit,
+        timeline: Vec::new(),
+    })
+}
+
+fn cognitive_summary(files: &BTreeMap<PathBuf, FileAnalysis>) -> CognitiveMetrics {
+    let mut summary = CognitiveMetrics::default();
+    let mut functions = 0usize;
+    let mut function_total = 0usize;
+    for (path, f) in files {
+        summary.total += f.metrics.cognitive;
+        summary.max_nesting = summary.max_nesting.max(f.metrics.max_nesting);
+        for func in f.syntax.iter().flat_map(|s| &s.functions) {
+            functions += 1;
+            function_total += func.cognitive;
+            if func.cognitive > summary.max_function {
+                summary.max_function = func.cognitive;
+                summary.max_function_path =
+                    Some(format!("{}#{}", path.to_string_lossy(), func.name));
+            }
+        }
+    }
+    if functions > 0 {
+        summary.per_function = function_total as f64 / functions as f64;
+    }
+    summary
+}
+
+/// Reads and analyzes one file: tokei's language and line counts plus the
+/// ryoiki metrics. tokei's breakdown replaces the plain line split of
+/// [`analyze_source`].
+pub(crate) fn analyze_file(p: &Path, bytes: &[u8]) -> io::Result<FileAnalysis> {
+    let tokei_config = Config::default();
+    let tokei_lang = tokei_language(p, bytes);
+    let lines = tokei_lang.map(|lang| {
+        let stats = lang.parse_from_slice(bytes, &tokei_config);
+        LineStats {
+            language: lang.to_string(),
+            code: stats.code,
+            comments: stats.comments,
+            blanks: stats.blanks,
+        }
+    });
+    let txt =
+        std::str::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let language = tokei_lang
+        .map(|l| language_id(&l.to_string()))
+        .or_else(|| language_from_path(p));
+    let mut analysis = analyze_source(language, txt);
+    if let Some(l) = &lines {
+        let m = &mut analysis.metrics;
+        m.code = l.code;
+        m.comments = l.comments;
+        m.blanks = l.blanks;
+        m.lines = l.code + l.comments + l.blanks;
+    }
+    analysis.lines = lines;
+    Ok(analysis)
+}
+
+/// Detects the tokei language of `bytes`, stored at `p`, from the file name,
+/// the extension or the shebang line of `bytes`. Unlike
+/// `LanguageType::from_path` it never opens `p`, so a blob from a past
+/// revision is classified by its own contents rather than the working tree's.
+fn tokei_language(p: &Path, bytes: &[u8]) -> Option<LanguageType> {
+    if p.extension().is_some() {
+        return LanguageType::from_path(p, &Config::default());
+    }
+    let name = p.file_name()?.to_string_lossy().to_lowercase();
+    let by_name = match name.as_str() {
+        "build" | "workspace" | "module" => Some(LanguageType::Bazel),
+        "dockerfile" => Some(LanguageType::Dockerfile),
+        "justfile" => Some(LanguageType::Just),
+        "gnumakefile" | "makefile" => Some(LanguageType::Makefile),
+        "pkgbuild" => Some(LanguageType::PacmanMakepkg),
+        "rakefile" => Some(LanguageType::Rakefile),
+        "sconstruct" | "sconscript" => Some(LanguageType::Scons),
+        "snakefile" => Some(LanguageType::Snakemake),
+        _ => None,
+    };
+    by_name.or_else(|| shebang_language(bytes))
+}
+
+/// The language named by a `#!` line: an interpreter path tokei knows, or
+/// the interpreter `/usr/bin/env` runs, with any version suffix dropped.
+fn shebang_language(bytes: &[u8]) -> Option<LanguageType> {
+    let line = bytes.split(|b| *b == b'\n').next()?;
+    let line = std::str::from_utf8(line).ok()?.trim_end();
+    if !line.starts_with("#!") {
+        return None;
+    }
+    if let Some((lang, _)) = LanguageType::list()
+        .iter()
+        .find(|(l, _)| l.shebangs().contains(&line))
+    {
+        return Some(*lang);
+    }
+    let mut words = line[2..].split_whitespace();
+    let mut interpreter = words.next()?.rsplit('/').next()?;
+    if interpreter == "env" {
+        interpreter = words.find(|w| !w.starts_with('-'))?;
+    }
+    interpreter_language(interpreter).or_else(|| {
+        interpreter_language(interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.'))
+    })
+}
+
+/// The language whose shebang runs `interpreter`, or whose name it is.
+fn interpreter_language(interpreter: &str) -> Option<LanguageType> {
+    LanguageType::list()
+        .iter()
+        .map(|(l, _)| *l)
+        .find(|l| {
+            l.shebangs().iter().any(|s| {
+                s.split_whitespace()
+                    .next()
+                    .and_then(|path| path.rsplit('/').next())
+                    == Some(interpreter)
+            })
+        })
+        .or_else(|| interpreter.parse().ok())
+}
+
+/// Groups a tokei language name into the coarse buckets used by the
+/// "By Category" report.
+pub fn classify(name: &str) -> &'static str {
+    match name {
+        "Rust" | "C" | "C++" | "C#" | "Go" | "Zig" | "Swift" | "Objective-C" | "Objective-C++" => {
+            "Systems"
+        }
+        "Python" | "Ruby" | "Perl" | "Lua" | "Shell" | "Bash" | "PowerShell" | "Fish" => {
+            "Scripting"
+        }
+        "JavaScript" | "TypeScript" | "JSX" | "TSX" | "Vue" | "Svelte" | "HTML" | "CSS"
+        | "Less" | "Sass" | "SCSS" => "Web",
+        "JSON" | "YAML" | "TOML" | "XML" | "EDN" | "RON" => "Config",
+        "Markdown" | "reStructuredText" | "Org" => "Docs",
+        "Makefile" | "CMake" | "Ninja" => "Build",
+        "SQL" | "Protobuf" | "GraphQL" => "Data",
+        _ => "Other",
+    }
+}
+
+fn root_gitignore(scan_root: &Path) -> Option<Arc<Gitignore>> {
+    let mut b = GitignoreBuilder::new(scan_root);
+    let root_ign = scan_root.join(".gitignore");
+    b.add(&root_ign);
+    if let Ok(home) = std::env::var("HOME").or_else(|_| std::env::var("USERPROFILE")) {
+        let global_ign = Path::new(&home).join(".gitignore_global");
+        if global_ign.exists() {
+            b.add(&global_ign);
+        }
+    }
+    b.build().ok().map(Arc::new)
+}
+
+/// Include and exclude globs as one override set; later globs win, so
+/// excludes beat includes.
+pub(crate) fn overrides(cfg: &ScanConfig) -> Result<Override, ScanError> {
+    let mut b = OverrideBuilder::new(&cfg.root);
+    for g in &cfg.includes {
+        b.add(g)
+            .map_err(|e| ScanError::InvalidGlob(e.to_string()))?;
+    }
+    for g in &cfg.excludes {
+        b.add(&format!("!{}", g))
+            .map_err(|e| ScanError::InvalidGlob(e.to_string()))?;
+    }
+    b.build().map_err(|e| ScanError::InvalidGlob(e.to_string()))
+}
+
+/// One walked entry, with paths relative to the scan root.
+pub(crate) enum Walked {
+    Dir(PathBuf),
+    File(PathBuf, io::Result<Box<CachedAnalysis>>),
+}
+
+/// Walks `cfg.root` once on all cores, analyzing every file on the thread
+/// that found it. The order of the result is unspecified.
+fn walk(
+    cfg: &ScanConfig,
+    gi: Option<Arc<Gitignore>>,
+    cache: &ScanCache,
+) -> Result<Vec<Walked>, ScanError> {
+    let scan_root = cfg.root.as_path();
+    let mut walker = WalkBuilder::new(scan_root);
+    walker
+        .git_ignore(true)
+        .git_global(true)
+        .git_exclude(true)
+        .hidden(!cfg.hidden)
+        .follow_links(cfg.follow_symlinks)
+        .max_filesize(cfg.max_file_size)
+        .overrides(overrides(cfg)?);
+    // `.gitignore` is honored outside git repositories too.
+    if let Some(gi) = gi {
+        walker.filter_entry(move |e| {
+            !gi.matched_path_or_any_parents(e.path(), e.file_type().is_some_and(|t| t.is_dir()))
+                .is_ignore()
+        });
+    }
+
+    let out: Mutex<Vec<Walked>> = Mutex::new(Vec::new());
+    walker.build_parallel().run(|| {
+        let out = &out;
+        Box::new(move |entry| {
+            let Ok(entry) = entry else {
+                return WalkState::Continue;
+            };
+            if entry.depth() == 0 {
+                return WalkState::Continue;
+            }
+            let p = entry.path();
+            let rel = p.strip_prefix(scan_root).unwrap_or(p).to_path_buf();
+            let walked = match entry.file_type() {
+                Some(t) if t.is_dir() => Walked::Dir(rel),
+                Some(t) if t.is_file() => {
+                    let result = cache.analyze(&rel.to_string_lossy(), p).map(Box::new);
+                    Walked::File(rel, result)
+                }
+                _ => return WalkState::Continue,
+            };
+            if let Ok(mut v) = out.lock() {
+                v.push(walked);
+            }
+            WalkState::Continue
+        })
+    });
+    Ok(out.into_inner().unwrap_or_default())
+}
+
+/// Builds the directory tree from the walk results. Children are sorted by
+/// name, so the output does not depend on the order workers finished in;
+/// functions stay in source order.
+fn assemble_tree(
+    scan_root: &Path,
+    dirs: &[PathBuf],
+    files: &BTreeMap<PathBuf, FileAnalysis>,
+    depth: ScanDepth,
+) -> Node {
+    #[derive(Default)]
+    struct Dir {
+        dirs: BTreeMap<String, Dir>,
+        files: Vec<(String, PathBuf)>,
+    }
+    fn dir_at<'a>(root: &'a mut Dir, rel: &Path) -> &'a mut Dir {
+        let mut cur = root;
+        for c in rel.components() {
+            let name = c.as_os_str().to_string_lossy().to_string();
+            cur = cur.dirs.entry(name).or_default();
+        }
+        cur
+    }
+
+    let mut root = Dir::default();
+    for d in dirs {
+        dir_at(&mut root, d);
+    }
+    for rel in files.keys() {
+        let (Some(parent), Some(name)) = (rel.parent(), rel.file_name()) else {
+            continue;
+        };
+        dir_at(&mut root, parent)
+            .files
+            .push((name.to_string_lossy().to_string(), rel.clone()));
+    }
+
+    fn build(
+        name: String,
+        rel: &Path,
+        dir: &Dir,
+        files: &BTreeMap<PathBuf, FileAnalysis>,
+        depth: ScanDepth,
+    ) -> Node {
+        let mut children: Vec<Node> = Vec::new();
+        let mut langs: HashMap<String, usize> = HashMap::new();
+        for (child_name, d) in &dir.dirs {
+            children.push(build(
+                child_name.clone(),
+                &rel.join(child_name),
+                d,
+                files,
+                depth,
+            ));
+        }
+        for (child_name, path) in &dir.files {
+            let a = &files[path];
+            let path = path.to_string_lossy().to_string();
+            let functions = match (depth, &a.syntax) {
+                (ScanDepth::Functions, Some(syntax)) => {
+                    Some(function_nodes(&path, a.language.as_deref(), syntax))
+                }
+                _ => None,
+            };
+            children.push(Node {
+                name: child_name.clone(),
+                language: a.language.clone(),
+                children: functions,
+                maintainability: a.maintainability.clone(),
+                ..Node::file(&path, a.metrics.clone())
+            });
+        }
+        children.sort_by(|a, b| a.name.cmp(&b.name));
+        for child in &children {
+            if let Some(l) = &child.language {
+                *langs.entry(l.clone()).or_default() += child.metrics.loc;
+            }
+        }
+        let path = if rel.as_os_str().is_empty() {
+            ".".to_string()
+        } else {
+            rel.to_string_lossy().to_string()
+        };
+        Node {
+            name,
+            language: dominant_language(&langs),
+            maintainability: Maintainability::below(&children),
+            ..Node::directory(&path, children)
+        }
+    }
+
+    // `./.` has no file name of its own; the canonical path does.
+    let root_name = scan_root
+        .canonicalize()
+        .ok()
+        .and_then(|p| p.file_name().map(|s| s.to_string_lossy().to_string()))
+        .unwrap_or_else(|| ".".to_string());
+    build(root_name, Path::new(""), &root, files, depth)
+}
+
+/// A `function` node per function of `syntax`, found in the file at `path`.
+/// Names that occur more than once in the file, such as overloads, get their
+/// line appended (`A::f:12`) so siblings stay distinct.
+fn function_nodes(path: &str, language: Option<&str>, syntax: &SyntaxAnalysis) -> Vec<Node> {
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    for f in &syntax.functions {
+        *seen.entry(f.name.as_str()).or_default() += 1;
+    }
+    syntax
+        .functions
+        .iter()
+        .map(|f| {
+            let name = if seen[f.name.as_str()] > 1 {
+                format!("{}:{}", f.name, f.line)
+            } else {
+                f.name.clone()
+            };
+            let loc = f.end_line.saturating_sub(f.line) + 1;
+            let metrics = Metrics {
+                loc,
+                complexity: f.complexity,
+                functions: 1,
+                lines: loc,
+                cognitive: f.cognitive,
+                max_nesting: f.nesting,
+                ..Metrics::default()
+            };
+            Node {
+                kind: "function".to_string(),
+                name: name.clone(),
+                language: language.map(str::to_string),
+                function: Some(FunctionSpan {
+                    line: f.line,
+                    end_line: f.end_line,
+                    params: f.params,
+                }),
+                ..Node::file(&format!("{}#{}", path, name), metrics)
+            }
+        })
+        .collect()
+}
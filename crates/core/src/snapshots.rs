@@ -0,0 +1,301 @@
+//! Scan snapshots kept in the metrics directory, for trends over time.
+//!
+//! [`SnapshotStore::save`] writes a report's tree and summary to
+//! `history/<id>.json` and records it in `history/index.json`. Listings and
+//! time series only read the index, so they stay cheap however large the
+//! trees are. Old snapshots are pruned per [`SnapshotOptions`].
+
+use crate::config::SnapshotOptions;
+use crate::report::{AdvancedMetrics, Metrics, Node, ScanCommit, ScanReport, Totals};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Directory of the store inside the metrics directory.
+pub const SNAPSHOT_DIR: &str = "history";
+const INDEX_FILE: &str = "index.json";
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// What the index records about a snapshot.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SnapshotMeta {
+    /// `<timestamp>-<commit id prefix>`, or just the timestamp outside git.
+    pub id: String,
+    /// Seconds since the epoch.
+    pub timestamp: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<ScanCommit>,
+    /// The metrics of the root node.
+    pub metrics: Metrics,
+    pub totals: Totals,
+    pub advanced: AdvancedMetrics,
+}
+
+/// A stored scan.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Snapshot {
+    #[serde(flatten)]
+    pub meta: SnapshotMeta,
+    pub tree: Node,
+}
+
+/// [`Snapshot`] without owning the tree, for writing.
+#[derive(Serialize)]
+struct SnapshotRef<'a> {
+    #[serde(flatten)]
+    meta: &'a SnapshotMeta,
+    tree: &'a Node,
+}
+
+/// One snapshot's value in [`SnapshotStore::series`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SeriesPoint {
+    pub id: String,
+    pub timestamp: u64,
+    /// The commit id, if the snapshot has one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    pub value: f64,
+}
+
+/// The snapshots of one metrics directory.
+pub struct SnapshotStore {
+    dir: PathBuf,
+}
+
+impl SnapshotStore {
+    pub fn new(metrics_dir: &Path) -> Self {
+        SnapshotStore {
+            dir: metrics_dir.join(SNAPSHOT_DIR),
+        }
+    }
+
+    /// Every stored snapshot, oldest first. A store that was never written
+    /// to is empty.
+    pub fn list(&self) -> io::Result<Vec<SnapshotMeta>> {
+        match fs::read(self.dir.join(INDEX_FILE)) {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// The snapshot `id`, or `None` when there is no such snapshot.
+    pub fn get(&self, id: &str) -> io::Result<Option<Snapshot>> {
+        // Ids never contain anything else, so no path can escape the store,
+        // and none names the index.
+        if id.is_empty()
+            || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+            || Some(id) == INDEX_FILE.strip_suffix(".json")
+        {
+            return Ok(None);
+        }
+        match fs::read(self.snapshot_path(id)) {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Stores `report` as taken at `timestamp` (seconds since the epoch),
+    /// then drops whatever `opts` no longer keeps, possibly including the new
+    /// snapshot itself when it is older than the rest.
+    pub fn save(
+        &self,
+        report: &ScanReport,
+        timestamp: u64,
+        opts: &SnapshotOptions,
+    ) -> io::Result<SnapshotMeta> {
+        let mut index = self.list()?;
+        let base = match &report.commit {
+            Some(c) => format!("{}-{}", timestamp, &c.id[..c.id.len().min(12)]),
+            None => timestamp.to_string(),
+        };
+        let mut id = base.clone();
+        for n in 2.. {
+            if !index.iter().any(|m| m.id == id) {
+                break;
+            }
+            id = format!("{}-{}", base, n);
+        }
+        let meta = SnapshotMeta {
+            id,
+            timestamp,
+            commit: report.commit.clone(),
+            metrics: report.tree.metrics.clone(),
+            totals: report.totals.clone(),
+            advanced: report.advanced.clone(),
+        };
+
+        fs::create_dir_all(&self.dir)?;
+        let snapshot = SnapshotRef {
+            meta: &meta,
+            tree: &report.tree,
+        };
+        fs::write(self.snapshot_path(&meta.id), serde_json::to_vec(&snapshot)?)?;
+        index.push(meta.clone());
+        index.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then_with(|| a.id.cmp(&b.id)));
+
+        let (kept, dropped) = retain(index, opts);
+        // Write the index first: a snapshot file without an index entry is
+        // merely wasted space, an entry without its file would be an error.
+        let tmp = self.dir.join(format!("{}.tmp", INDEX_FILE));
+        fs::write(&tmp, serde_json::to_vec_pretty(&kept)?)?;
+        fs::rename(&tmp, self.dir.join(INDEX_FILE))?;
+        for m in dropped {
+            let _ = fs::remove_file(self.snapshot_path(&m.id));
+        }
+        Ok(meta)
+    }
+
+    /// `metric` of every snapshot, oldest first, or `None` for an unknown
+    /// metric. `metric` is a dotted path into [`SnapshotMeta`] such as
+    /// `advanced.maintainability_index` or `totals.code`; a bare name is
+    /// looked up in `metrics`, `totals` and `advanced`, in that order, so
+    /// `loc` means `metrics.loc`.
+    pub fn series(&self, metric: &str) -> io::Result<Option<Vec<SeriesPoint>>> {
+        if lookup(&serde_json::to_value(SnapshotMeta::default())?, metric).is_none() {
+            return Ok(None);
+        }
+        let mut points = Vec::new();
+        for meta in self.list()? {
+            if let Some(value) = lookup(&serde_json::to_value(&meta)?, metric) {
+                points.push(SeriesPoint {
+                    id: meta.id,
+                    timestamp: meta.timestamp,
+                    commit: meta.commit.map(|c| c.id),
+                    value,
+                });
+            }
+        }
+        Ok(Some(points))
+    }
+
+    fn snapshot_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+}
+
+/// Splits `index` (sorted oldest first) into the snapshots `opts` keeps and
+/// the ones it drops.
+fn retain(
+    index: Vec<SnapshotMeta>,
+    opts: &SnapshotOptions,
+) -> (Vec<SnapshotMeta>, Vec<SnapshotMeta>) {
+    let newest = index.last().map_or(0, |m| m.timestamp);
+    let (mut kept, mut dropped): (Vec<_>, Vec<_>) = index.into_iter().partition(|m| {
+        opts.max_age_days
+            .is_none_or(|days| newest.saturating_sub(m.timestamp) <= days * SECONDS_PER_DAY)
+    });
+    if let Some(keep) = opts.keep
+        && kept.len() > keep
+    {
+        dropped.extend(kept.drain(..kept.len() - keep));
+    }
+    (kept, dropped)
+}
+
+fn lookup(meta: &serde_json::Value, metric: &str) -> Option<f64> {
+    if metric.contains('.') {
+        return meta
+            .pointer(&format!("/{}", metric.replace('.', "/")))?
+            .as_f64();
+    }
+    ["metrics", "totals", "advanced"]
+        .iter()
+        .find_map(|section| meta.get(section)?.get(metric)?.as_f64())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(loc: usize, mi: f64) -> ScanReport {
+        ScanReport {
+            tree: Node {
+                name: "p".to_string(),
+                metrics: Metrics {
+                    loc,
+                    ..Metrics::default()
+                },
+                ..Node::directory(".", Vec::new())
+            },
+            totals: Totals::default(),
+            languages: Vec::new(),
+            categories: Vec::new(),
+            audit: Vec::new(),
+            advanced: AdvancedMetrics {
+                maintainability_index: mi,
+                ..AdvancedMetrics::default()
+            },
+            diagnostics: Vec::new(),
+            cache: None,
+            hotspots: Vec::new(),
+            commit: Some(ScanCommit {
+                id: "0123456789abcdef0123".to_string(),
+                time: 0,
+            }),
+            timeline: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_snapshot_store_saves_prunes_and_builds_series() {
+        let dir = std::env::temp_dir().join(format!("ryoiki-snapshots-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let store = SnapshotStore::new(&dir);
+        let opts = SnapshotOptions {
+            keep: Some(3),
+            max_age_days: Some(10),
+        };
+        assert!(store.list().expect("list").is_empty());
+
+        let day = SECONDS_PER_DAY;
+        let first = store.save(&report(10, 80.0), day, &opts).expect("save");
+        assert_eq!(first.id, format!("{}-0123456789ab", day));
+        let again = store.save(&report(10, 80.0), day, &opts).expect("save");
+        assert_eq!(again.id, format!("{}-2", first.id));
+        for (i, loc) in [20, 30, 40].into_iter().enumerate() {
+            store
+                .save(&report(loc, 70.0), (5 + i as u64) * day, &opts)
+                .expect("save");
+        }
+        store
+            .save(&report(50, 60.0), 14 * day, &opts)
+            .expect("save");
+
+        // Three kept by count; the day-1 ones are also past ten days.
+        let ids: Vec<u64> = store
+            .list()
+            .expect("list")
+            .iter()
+            .map(|m| m.timestamp / day)
+            .collect();
+        assert_eq!(ids, [6, 7, 14]);
+        assert!(store.get(&first.id).expect("get").is_none());
+
+        let loc: Vec<f64> = store
+            .series("loc")
+            .expect("series")
+            .expect("known metric")
+            .iter()
+            .map(|p| p.value)
+            .collect();
+        assert_eq!(loc, [30.0, 40.0, 50.0]);
+        let mi = store
+            .series("advanced.maintainability_index")
+            .expect("series")
+            .expect("known metric");
+        assert_eq!(mi[2].value, 60.0);
+        assert_eq!(mi[2].commit.as_deref(), Some("0123456789abcdef0123"));
+        assert!(store.series("nope").expect("series").is_none());
+
+        let newest = store.list().expect("list").pop().expect("snapshot");
+        let snapshot = store.get(&newest.id).expect("get").expect("stored");
+        assert_eq!(snapshot.tree.metrics.loc, 50);
+        assert!(store.get("../index").expect("get").is_none());
+        assert!(store.get("index").expect("get").is_none());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,786 @@
+//! Syntax-tree analysis for C-like and JVM languages with tree-sitter.
+//!
+//! Each supported language is a [`Grammar`]: the parser plus the node kinds
+//! that define functions, name scopes, branch, jump or nest. [`analyze`] walks
+//! the tree once and fills in a [`SyntaxAnalysis`] the same way
+//! [`crate::rust_ast`] does for Rust, so words such as `format` or `notify`
+//! no longer count as loops or conditionals. Adding a language means adding
+//! a grammar crate and a table entry in [`grammar`].
+//!
+//! tree-sitter recovers from syntax errors, so a file that does not fully
+//! parse is still analyzed; only the broken region may be miscounted.
+
+use crate::analysis::{FunctionAnalysis, SyntaxAnalysis};
+use tree_sitter::{Language, Node, Parser};
+
+/// How one language's syntax tree is read. Every field lists node kinds.
+struct Grammar {
+    language: fn() -> Language,
+    /// Function definitions. Those without a body (declarations, abstract
+    /// and interface methods) are skipped; see [`has_body`].
+    functions: &'static [&'static str],
+    /// Named containers whose `name` prefixes the functions inside.
+    scopes: &'static [&'static str],
+    /// One decision point and one level of nesting each: conditionals,
+    /// loops, `catch` and ternaries.
+    branches: &'static [&'static str],
+    /// Lambdas and anonymous functions: a decision point and a level of
+    /// nesting, but no cognitive complexity of their own.
+    lambdas: &'static [&'static str],
+    /// One decision point each unless their `pattern`, or else their first
+    /// token, is `default`, `else` or `_`: switch cases and guards.
+    cases: &'static [&'static str],
+    /// One level of nesting each, without a decision point: `switch`.
+    nesting: &'static [&'static str],
+    /// Binary expressions; one decision point for `&&`, `||`, `and` and
+    /// `or`, or for every one without an operator.
+    logical: &'static [&'static str],
+    /// `goto` and `break` / `continue`; cognitive complexity when they
+    /// name a label or level. Kotlin's `break@outer` is a labeled
+    /// expression, which only counts with a `break@` or `continue@` label.
+    jumps: &'static [&'static str],
+}
+
+fn grammar(lang: &str) -> Option<&'static Grammar> {
+    static JAVA: Grammar = Grammar {
+        language: || tree_sitter_java::LANGUAGE.into(),
+        functions: &[
+            "method_declaration",
+            "constructor_declaration",
+            "compact_constructor_declaration",
+        ],
+        scopes: &[
+            "class_declaration",
+            "interface_declaration",
+            "enum_declaration",
+            "record_declaration",
+        ],
+        branches: &[
+            "if_statement",
+            "for_statement",
+            "enhanced_for_statement",
+            "while_statement",
+            "do_statement",
+            "catch_clause",
+            "ternary_expression",
+        ],
+        lambdas: &["lambda_expression"],
+        cases: &["switch_label", "guard"],
+        nesting: &["switch_expression"],
+        logical: &["binary_expression"],
+        jumps: &["break_statement", "continue_statement"],
+    };
+    static CSHARP: Grammar = Grammar {
+        language: || tree_sitter_c_sharp::LANGUAGE.into(),
+        functions: &[
+            "method_declaration",
+            "constructor_declaration",
+            "destructor_declaration",
+            "operator_declaration",
+            "conversion_operator_declaration",
+            "local_function_statement",
+        ],
+        scopes: &[
+            "namespace_declaration",
+            "class_declaration",
+            "struct_declaration",
+            "interface_declaration",
+            "record_declaration",
+        ],
+        branches: &[
+            "if_statement",
+            "for_statement",
+            "foreach_statement",
+            "while_statement",
+            "do_statement",
+            "catch_clause",
+            "conditional_expression",
+        ],
+        lambdas: &["lambda_expression", "anonymous_method_expression"],
+        cases: &[
+            "switch_section",
+            "switch_expression_arm",
+            "when_clause",
+            "catch_filter_clause",
+        ],
+        nesting: &["switch_statement", "switch_expression"],
+        logical: &["binary_expression"],
+        jumps: &["goto_statement"],
+    };
+    static C: Grammar = Grammar {
+        language: || tree_sitter_c::LANGUAGE.into(),
+        functions: &["function_definition"],
+        scopes: &[],
+        branches: &[
+            "if_statement",
+            "for_statement",
+            "while_statement",
+            "do_statement",
+            "conditional_expression",
+        ],
+        lambdas: &[],
+        cases: &["case_statement"],
+        nesting: &["switch_statement"],
+        logical: &["binary_expression"],
+        jumps: &["goto_statement"],
+    };
+    static CPP: Grammar = Grammar {
+        language: || tree_sitter_cpp::LANGUAGE.into(),
+        functions: &["function_definition"],
+        scopes: &[
+            "namespace_definition",
+            "class_specifier",
+            "struct_specifier",
+        ],
+        branches: &[
+            "if_statement",
+            "for_statement",
+            "for_range_loop",
+            "while_statement",
+            "do_statement",
+            "conditional_expression",
+            "catch_clause",
+        ],
+        lambdas: &["lambda_expression"],
+        cases: &["case_statement"],
+        nesting: &["switch_statement"],
+        logical: &["binary_expression"],
+        jumps: &["goto_statement"],
+    };
+    static SWIFT: Grammar = Grammar {
+        language: || tree_sitter_swift::LANGUAGE.into(),
+        functions: &[
+            "function_declaration",
+            "init_declaration",
+            "deinit_declaration",
+        ],
+        scopes: &["class_declaration", "protocol_declaration"],
+        branches: &[
+            "if_statement",
+            "guard_statement",
+            "for_statement",
+            "while_statement",
+            "repeat_while_statement",
+            "catch_block",
+            "ternary_expression",
+        ],
+        lambdas: &["lambda_literal"],
+        cases: &["switch_entry"],
+        nesting: &["switch_statement"],
+        logical: &["conjunction_expression", "disjunction_expression"],
+        jumps: &[],
+    };
+    static PHP: Grammar = Grammar {
+        language: || tree_sitter_php::LANGUAGE_PHP.into(),
+        functions: &["function_definition", "method_declaration"],
+        scopes: &[
+            "namespace_definition",
+            "class_declaration",
+            "interface_declaration",
+            "trait_declaration",
+            "enum_declaration",
+        ],
+        branches: &[
+            "if_statement",
+            "for_statement",
+            "foreach_statement",
+            "while_statement",
+            "do_statement",
+            "catch_clause",
+            "conditional_expression",
+        ],
+        lambdas: &["anonymous_function", "arrow_function"],
+        cases: &[
+            "case_statement",
+            "else_if_clause",
+            "match_conditional_expression",
+        ],
+        nesting: &["switch_statement", "match_expression"],
+        logical: &["binary_expression"],
+        jumps: &["goto_statement", "break_statement", "continue_statement"],
+    };
+    static KOTLIN: Grammar = Grammar {
+        language: || tree_sitter_kotlin_ng::LANGUAGE.into(),
+        functions: &["function_declaration", "secondary_constructor"],
+        scopes: &[
+            "class_declaration",
+            "object_declaration",
+            "companion_object",
+        ],
+        branches: &[
+            "if_expression",
+            "for_statement",
+            "while_statement",
+            "do_while_statement",
+            "catch_block",
+        ],
+        lambdas: &["lambda_literal", "anonymous_function"],
+        cases: &["when_entry"],
+        nesting: &["when_expression"],
+        logical: &["binary_expression"],
+        jumps: &["labeled_expression"],
+    };
+    static SCALA: Grammar = Grammar {
+        language: || tree_sitter_scala::LANGUAGE.into(),
+        functions: &["function_definition"],
+        scopes: &[
+            "class_definition",
+            "object_definition",
+            "trait_definition",
+            "enum_definition",
+        ],
+        branches: &[
+            "if_expression",
+            "for_expression",
+            "while_expression",
+            "do_while_expression",
+        ],
+        lambdas: &["lambda_expression"],
+        cases: &["case_clause", "guard"],
+        // A `catch` decides through its cases.
+        nesting: &["match_expression", "catch_clause"],
+        logical: &["infix_expression"],
+        jumps: &[],
+    };
+    Some(match lang {
+        "java" => &JAVA,
+        "csharp" => &CSHARP,
+        "c" => &C,
+        "cpp" => &CPP,
+        "swift" => &SWIFT,
+        "php" => &PHP,
+        "kotlin" => &KOTLIN,
+        "scala" => &SCALA,
+        _ => return None,
+    })
+}
+
+/// Parses `txt` as `lang`, a palette id such as `java`; `None` for
+/// languages without a grammar.
+pub(crate) fn analyze(lang: &str, txt: &str) -> Option<SyntaxAnalysis> {
+    let grammar = grammar(lang)?;
+    let mut parser = Parser::new();
+    parser.set_language(&(grammar.language)()).ok()?;
+    let tree = parser.parse(txt, None)?;
+    let mut w = Walker {
+        grammar,
+        src: txt.as_bytes(),
+        syntax: SyntaxAnalysis::default(),
+        open: Vec::new(),
+        scope: Vec::new(),
+        depth: 0,
+    };
+    w.walk(tree.root_node());
+    Some(w.syntax)
+}
+
+struct Walker<'a> {
+    grammar: &'static Grammar,
+    src: &'a [u8],
+    syntax: SyntaxAnalysis,
+    /// Indices into `syntax.functions` of the functions being walked,
+    /// innermost last.
+    open: Vec<usize>,
+    /// Enclosing namespaces and classes.
+    scope: Vec<String>,
+    /// Nesting inside the innermost open function, or at file level.
+    depth: usize,
+}
+
+impl<'a> Walker<'a> {
+    fn text(&self, node: Node<'_>) -> &'a str {
+        node.utf8_text(self.src).unwrap_or("")
+    }
+
+    fn decide(&mut self) {
+        self.syntax.decisions += 1;
+        if let Some(&i) = self.open.last() {
+            self.syntax.functions[i].complexity += 1;
+        }
+    }
+
+    fn cognitive(&mut self, n: usize) {
+        self.syntax.cognitive += n;
+        if let Some(&i) = self.open.last() {
+            self.syntax.functions[i].cognitive += n;
+        }
+    }
+
+    /// The operator of a binary expression: its `operator` field or, in C#,
+    /// its first unnamed child. `None` for grammars with a node kind per
+    /// operator.
+    fn operator(&self, node: Node<'_>) -> Option<&'a str> {
+        let mut cursor = node.walk();
+        node.child_by_field_name("operator")
+            .or_else(|| node.children(&mut cursor).find(|c| !c.is_named()))
+            .map(|o| self.text(o))
+    }
+
+    fn walk(&mut self, node: Node<'_>) {
+        let g = self.grammar;
+        let kind = node.kind();
+        if g.functions.contains(&kind) && has_body(node) {
+            return self.function(node);
+        }
+        if g.scopes.contains(&kind)
+            && let Some(name) = node.child_by_field_name("name")
+        {
+            self.scope.push(self.text(name).to_string());
+            self.walk_children(node);
+            self.scope.pop();
+            return;
+        }
+
+        let mut nests = g.nesting.contains(&kind);
+        if nests {
+            self.cognitive(1 + self.depth);
+        } else if g.branches.contains(&kind) {
+            self.decide();
+            nests = !is_else_if(node);
+            self.cognitive(if nests { 1 + self.depth } else { 1 });
+            self.cognitive(plain_elses(node));
+        } else if g.lambdas.contains(&kind) {
+            self.decide();
+            nests = true;
+        } else if g.cases.contains(&kind) {
+            let first = node
+                .child_by_field_name("pattern")
+                .or_else(|| node.child(0))
+                .map(|c| self.text(c));
+            if !matches!(first, Some("default" | "else" | "_")) {
+                self.decide();
+            }
+        } else if g.logical.contains(&kind) {
+            let op = self.operator(node);
+            if op.is_none_or(|op| matches!(op, "&&" | "||" | "and" | "or")) {
+                self.decide();
+                // A run of like operators counts once.
+                let continues = node
+                    .parent()
+                    .is_some_and(|p| p.kind() == kind && self.operator(p) == op);
+                if !continues {
+                    self.cognitive(1);
+                }
+            }
+        } else if g.jumps.contains(&kind)
+            && node.named_child_count() > 0
+            && node.named_child(0).is_none_or(|label| {
+                label.kind() != "label" || matches!(self.text(label), "break@" | "continue@")
+            })
+        {
+            self.cognitive(1);
+        }
+
+        if nests {
+            self.depth += 1;
+            self.syntax.max_nesting = self.syntax.max_nesting.max(self.depth);
+            if let Some(&i) = self.open.last() {
+                let f = &mut self.syntax.functions[i];
+                f.nesting = f.nesting.max(self.depth);
+            }
+        }
+        self.walk_children(node);
+        if nests {
+            self.depth -= 1;
+        }
+    }
+
+    fn walk_children(&mut self, node: Node<'_>) {
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            self.walk(child);
+        }
+    }
+
+    fn function(&mut self, node: Node<'_>) {
+        let name_node = function_name(node);
+        let name = self
+            .scope
+            .iter()
+            .map(String::as_str)
+            .chain([name_node.map_or_else(
+                || {
+                    node.kind()
+                        .trim_end_matches("_declaration")
+                        .trim_start_matches("secondary_")
+                },
+                |n| self.text(n),
+            )])
+            .collect::<Vec<_>>()
+            .join("::");
+        self.open.push(self.syntax.functions.len());
+        self.syntax.functions.push(FunctionAnalysis {
+            name,
+            line: name_node.unwrap_or(node).start_position().row + 1,
+            end_line: node.end_position().row + 1,
+            params: parameter_count(node, self.src),
+            complexity: 1,
+            cognitive: 0,
+            nesting: 0,
+        });
+        let depth = std::mem::take(&mut self.depth);
+        self.walk_children(node);
+        self.depth = depth;
+        self.open.pop();
+    }
+}
+
+/// Whether a function has a body: a `body` field or, in Kotlin, a
+/// `function_body` or constructor `block`.
+fn has_body(node: Node<'_>) -> bool {
+    let mut cursor = node.walk();
+    node.child_by_field_name("body").is_some()
+        || node
+            .named_children(&mut cursor)
+            .any(|c| matches!(c.kind(), "function_body" | "block"))
+}
+
+/// The `name` of a function, or for C and C++ the innermost of its nested
+/// `declarator`s (`*f`, `f(int)`, `Class::f`).
+fn function_name(node: Node<'_>) -> Option<Node<'_>> {
+    let mut n = node;
+    loop {
+        if let Some(name) = n.child_by_field_name("name") {
+            return Some(name);
+        }
+        match n.child_by_field_name("declarator") {
+            Some(d) => n = d,
+            None => return (n != node).then_some(n),
+        }
+    }
+}
+
+/// The parameters of a function: those of its `parameters` lists, found on
+/// the function or along its `declarator`s, of Kotlin's
+/// `function_value_parameters`, or Swift's `parameter` children. Scala's
+/// curried lists add up; type parameters do not count. C's `(void)` and
+/// Java's explicit `this` receiver count none; a C# `params` array is a
+/// `name` on the list itself.
+fn parameter_count(node: Node<'_>, src: &[u8]) -> usize {
+    let mut n = node;
+    let lists = loop {
+        let (mut fields, mut children) = (n.walk(), n.walk());
+        let lists: Vec<Node<'_>> = n
+            .children_by_field_name("parameters", &mut fields)
+            .filter(|l| l.kind() != "type_parameters")
+            .chain(
+                n.named_children(&mut children)
+                    .filter(|c| c.kind() == "function_value_parameters"),
+            )
+            .collect();
+        if !lists.is_empty() {
+            break lists;
+        }
+        match n.child_by_field_name("declarator") {
+            Some(d) => n = d,
+            None => break vec![node],
+        }
+    };
+    lists
+        .into_iter()
+        .map(|list| {
+            let mut cursor = list.walk();
+            let params = list
+                .named_children(&mut cursor)
+                .filter(|p| p.kind().contains("parameter") && p.kind() != "receiver_parameter")
+                .filter(|p| p.utf8_text(src) != Ok("void"))
+                .count();
+            params + usize::from(list != node && list.child_by_field_name("name").is_some())
+        })
+        .sum()
+}
+
+/// The `else` branches of an `if` that are not `else if`s, which count on
+/// their own: one for a plain `else`, one per PHP `elseif`. The `else` is an
+/// `alternative` field, wrapped in an `else_clause` in C, C++ and PHP, or in
+/// Swift and Kotlin the node after an `else`.
+fn plain_elses(node: Node<'_>) -> usize {
+    if !is_if(node) {
+        return 0;
+    }
+    let mut cursor = node.walk();
+    let mut alternatives: Vec<Node<'_>> = node
+        .children_by_field_name("alternative", &mut cursor)
+        .collect();
+    if alternatives.is_empty() {
+        alternatives.extend(
+            node.children(&mut cursor)
+                .filter(|c| c.kind() == "else")
+                .filter_map(|c| c.next_named_sibling()),
+        );
+    }
+    alternatives
+        .into_iter()
+        .map(|a| match a.kind() {
+            "else_clause" => a.child_by_field_name("body").or_else(|| a.named_child(0)),
+            _ => Some(a),
+        })
+        .filter(|a| a.is_none_or(|a| !is_if(a)))
+        .count()
+}
+
+/// An `if` statement or, in Kotlin and Scala, expression.
+fn is_if(node: Node<'_>) -> bool {
+    matches!(node.kind(), "if_statement" | "if_expression")
+}
+
+/// An `if` in the `else` of another stays at the nesting level of the
+/// first.
+fn is_else_if(node: Node<'_>) -> bool {
+    is_if(node)
+        && node
+            .parent()
+            .is_some_and(|p| is_if(p) || p.kind() == "else_clause")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn functions(syntax: &SyntaxAnalysis) -> Vec<(&str, usize, usize)> {
+        syntax
+            .functions
+            .iter()
+            .map(|f| (f.name.as_str(), f.complexity, f.nesting))
+            .collect()
+    }
+
+    #[test]
+    fn test_java_methods_cases_and_identifiers() {
+        let src = r#"
+class Notifier {
+    Notifier() {}
+    abstract static class Base { abstract void run(); }
+    int notify(String format, boolean verify) {
+        for (int i = 0; i < 3; i++) {
+            if (verify && format.isEmpty()) { return 1; } else if (i > 1) { return 2; }
+        }
+        switch (format) {
+            case "a": return 3;
+            case "b": return 4;
+            default: return verify ? 5 : 6;
+        }
+    }
+}
+"#;
+        let syntax = analyze("java", src).expect("java grammar");
+        // for, if, &&, else if, 2 cases, ternary.
+        assert_eq!(
+            functions(&syntax),
+            [("Notifier::Notifier", 1, 0), ("Notifier::notify", 8, 2)]
+        );
+        assert_eq!(syntax.functions[1].line, 5);
+        assert_eq!(syntax.max_nesting, 2);
+    }
+
+    #[test]
+    fn test_c_family_grammars() {
+        let c = analyze(
+            "c",
+            "static int *f(int x) { while (x) { x--; } return x > 0 ? &x : 0; }\nint g(void);\n",
+        )
+        .expect("c grammar");
+        assert_eq!(functions(&c), [("f", 3, 1)]);
+
+        let cpp = analyze(
+            "cpp",
+            "namespace n { struct S { int m(int x) { return x || [](){ return 1; }(); } }; }\n",
+        )
+        .expect("cpp grammar");
+        assert_eq!(functions(&cpp), [("n::S::m", 3, 1)]);
+
+        let cs = analyze(
+            "csharp",
+            "class A { int F(int x) { try { return x; } catch (E e) when (x > 0) { return 0; } } }\n",
+        )
+        .expect("c# grammar");
+        assert_eq!(functions(&cs), [("A::F", 3, 1)]);
+
+        let php = analyze(
+            "php",
+            "<?php\nfunction f($a) { if ($a and $b) { return 1; } elseif ($b) { return 2; } }\n",
+        )
+        .expect("php grammar");
+        assert_eq!(functions(&php), [("f", 4, 1)]);
+
+        let swift = analyze(
+            "swift",
+            "class V {\n  init() {}\n  func f(x: Int) -> Int {\n    guard x > 0 else { return 0 }\n    return x\n  }\n}\n",
+        )
+        .expect("swift grammar");
+        assert_eq!(functions(&swift), [("V::init", 1, 0), ("V::f", 2, 1)]);
+
+        assert!(analyze("go", "func f() {}").is_none());
+    }
+
+    #[test]
+    fn test_kotlin_and_scala_grammars() {
+        let kotlin = r#"
+class A {
+    companion object Named {
+        fun z() = 1
+    }
+    constructor(x: Int) : this() {}
+    fun f(a: Int, b: String): Int {
+        outer@ for (i in 1..3) {
+            if (a > 1 && b.isEmpty() || a < 0) {
+                break@outer
+            } else if (a == 2) {
+                continue
+            } else {
+                return 1
+            }
+        }
+        val r = when (a) {
+            1, 2 -> 3
+            else -> 5
+        }
+        list.map { it * 2 }
+        return if (a > 0) 1 else 2
+    }
+}
+interface I {
+    fun abs(): Int
+}
+"#;
+        let syntax = analyze("kotlin", kotlin).expect("kotlin grammar");
+        // for, if, &&, ||, else if, one `when` entry, the lambda, the `if`
+        // expression; `else ->` does not decide.
+        assert_eq!(
+            functions(&syntax),
+            [
+                ("A::Named::z", 1, 0),
+                ("A::constructor", 1, 0),
+                ("A::f", 9, 2)
+            ]
+        );
+        let params: Vec<usize> = syntax.functions.iter().map(|f| f.params).collect();
+        assert_eq!(params, [0, 1, 2]);
+        // for 1, if 2, && and || 2, labeled break 1, else if 1, else 1,
+        // when 1, `if` expression and its else 2.
+        assert_eq!(syntax.functions[2].cognitive, 11);
+
+        let scala = r#"
+object O {
+  class C(x: Int) {
+    def f[T](a: Int, b: T)(c: Int): Int = {
+      if (a > 1 && c < 2 || a == 0) 1 else if (a == 2) 2 else 3
+      a match {
+        case 1 => 1
+        case x if x > 2 => 2
+        case _ => 3
+      }
+      try g() catch { case e: Exception => 0 }
+      List(1).map(x => x * 2)
+    }
+    def abs(x: Int): Int
+  }
+}
+"#;
+        let syntax = analyze("scala", scala).expect("scala grammar");
+        // if, &&, ||, else if, two cases and a guard, the catch case, the
+        // lambda; `case _` does not decide.
+        assert_eq!(functions(&syntax), [("O::C::f", 10, 1)]);
+        assert_eq!(syntax.functions[0].params, 3);
+    }
+
+    #[test]
+    fn test_cognitive_complexity() {
+        let cognitive = |lang: &str, src: &str| {
+            analyze(lang, src)
+                .expect("grammar")
+                .functions
+                .iter()
+                .map(|f| f.cognitive)
+                .collect::<Vec<_>>()
+        };
+        // for 1, for 2, if 3, labeled continue 1.
+        let java = "class A {\n  int sumOfPrimes(int max) {\n    int total = 0;\n    \
+                    out: for (int i = 1; i <= max; ++i) {\n      \
+                    for (int j = 2; j < i; ++j) {\n        \
+                    if (i % j == 0) { continue out; }\n      }\n      total += i;\n    }\n    \
+                    return total;\n  }\n  \
+                    String words(int n) {\n    switch (n) {\n      case 1: return \"one\";\n      \
+                    default: return \"lots\";\n    }\n  }\n}\n";
+        assert_eq!(cognitive("java", java), [7, 1]);
+        // if 1, && 1, || 1, else if 1, else 1, ternary in a lambda 2.
+        let c_like = "if (a && b && c || d) { x(); } else if (e) { y(); } else { z(); }";
+        assert_eq!(
+            cognitive(
+                "cpp",
+                &format!(
+                    "void f() {{ {} auto g = [](){{ return a ? 1 : 0; }}; }}",
+                    c_like
+                )
+            ),
+            [7]
+        );
+        assert_eq!(
+            cognitive("c", &format!("void f() {{ {} goto end; end: ; }}", c_like)),
+            [6]
+        );
+        assert_eq!(
+            cognitive(
+                "csharp",
+                &format!("class A {{ void F() {{ {} }} }}", c_like)
+            ),
+            [5]
+        );
+        assert_eq!(
+            cognitive(
+                "php",
+                "<?php\nfunction f() { if ($a) {} elseif ($b) {} else {} }"
+            ),
+            [3]
+        );
+        assert_eq!(
+            cognitive(
+                "swift",
+                "func f() { if a && b { x() } else if c { y() } else { z() } }"
+            ),
+            [4]
+        );
+    }
+
+    #[test]
+    fn test_function_spans_and_parameters() {
+        let spans = |lang: &str, src: &str| {
+            analyze(lang, src)
+                .expect("grammar")
+                .functions
+                .iter()
+                .map(|f| (f.line, f.end_line, f.params))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            spans(
+                "java",
+                "class A {\n  void f(A this, int a, String... b) {\n  }\n}\n"
+            ),
+            [(2, 3, 2)]
+        );
+        assert_eq!(
+            spans(
+                "c",
+                "int f(void) { return 0; }\nint g(int a, ...)\n{\n  return a;\n}\n"
+            ),
+            [(1, 1, 0), (2, 5, 2)]
+        );
+        assert_eq!(
+            spans("cpp", "int S::m(int a, int b = 1) { return a; }\n"),
+            [(1, 1, 2)]
+        );
+        assert_eq!(
+            spans("csharp", "class A { void F(int a, params int[] b) {} }\n"),
+            [(1, 1, 2)]
+        );
+        assert_eq!(
+            spans("php", "<?php\nfunction f($a, ...$b) {\n}\n"),
+            [(2, 3, 2)]
+        );
+        assert_eq!(
+            spans("swift", "func f(x: Int, y: Int) -> Int {\n  return x\n}\n"),
+            [(1, 3, 2)]
+        );
+    }
+}
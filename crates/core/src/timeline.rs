@@ -0,0 +1,209 @@
+//! Replaying git history into a metrics timeline.
+//!
+//! [`run_timeline`] follows the first-parent chain from
+//! [`TimelineOptions::to`] back to [`TimelineOptions::from`], picks revisions
+//! along it per [`TimelineStep`] and scans each one from the object database,
+//! oldest first. The whole run shares one [`ScanCache`], so a blob that is
+//! unchanged since an earlier revision is not analyzed again.
+
+use crate::cache::ScanCache;
+use crate::config::ScanConfig;
+use crate::history::{resolve_commit, scan_commit};
+use crate::report::{ScanCommit, ScanReport, TimelinePoint};
+use crate::scan::{ScanError, scan_with_cache};
+use std::fmt;
+use std::str::FromStr;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// How far apart the revisions of a timeline are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimelineStep {
+    /// Every n-th commit.
+    Commits(usize),
+    /// The first commit at least n days after the previous pick.
+    Days(u64),
+}
+
+impl FromStr for TimelineStep {
+    type Err = String;
+
+    /// `10`, `10c` or `10 commits`; `30d` or `30 days`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (n, unit) = s.split_at(split);
+        let invalid = || format!("expected <n>, <n> commits or <n> days, got {:?}", s);
+        let n: u64 = n.parse().map_err(|_| invalid())?;
+        if n == 0 {
+            return Err("the step must be at least 1".to_string());
+        }
+        match unit.trim() {
+            "" | "c" | "commit" | "commits" => Ok(TimelineStep::Commits(n as usize)),
+            "d" | "day" | "days" => Ok(TimelineStep::Days(n)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl fmt::Display for TimelineStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimelineStep::Commits(n) => write!(f, "{} commits", n),
+            TimelineStep::Days(n) => write!(f, "{} days", n),
+        }
+    }
+}
+
+/// Which revisions [`run_timeline`] scans.
+#[derive(Debug, Clone)]
+pub struct TimelineOptions {
+    /// The oldest revision, a first-parent ancestor of `to`.
+    pub from: String,
+    /// The newest revision; `HEAD` when `None`.
+    pub to: Option<String>,
+    pub every: TimelineStep,
+}
+
+/// Scans the revisions `opts` picks, oldest first, passing each report to
+/// `each`. Returns the report of the newest revision, whose
+/// [`ScanReport::timeline`] holds the summary metrics of all of them.
+///
+/// Only the newest revision is scanned with [`ScanConfig::history`]; reading
+/// the history again for every older one would cost more than the scans. The
+/// cache at [`ScanConfig::cache_path`] is read but not written, as it
+/// describes the working tree.
+pub fn run_timeline(
+    cfg: &ScanConfig,
+    opts: &TimelineOptions,
+    mut each: impl FnMut(&ScanReport),
+) -> Result<ScanReport, ScanError> {
+    let revisions = revisions(cfg, opts)?;
+    let Some((newest, older)) = revisions.split_last() else {
+        return Err(ScanError::Revision(
+            opts.from.clone(),
+            "no revisions to scan".to_string(),
+        ));
+    };
+    let mut cache = match &cfg.cache_path {
+        Some(path) => ScanCache::load(path, cfg),
+        None => ScanCache::new(cfg),
+    };
+    let mut timeline = Vec::with_capacity(revisions.len());
+    let mut scan = |commit: &ScanCommit, with_history: bool| {
+        let mut rev_cfg = cfg.clone();
+        rev_cfg.revision = Some(commit.id.clone());
+        if !with_history {
+            rev_cfg.history = None;
+        }
+        let mut report = scan_with_cache(&rev_cfg, &mut cache)?;
+        report.cache = Some(cache.stats().clone());
+        cache.carry_over();
+        each(&report);
+        timeline.push(TimelinePoint {
+            commit: commit.clone(),
+            totals: report.totals.clone(),
+            advanced: report.advanced.clone(),
+        });
+        Ok::<_, ScanError>(report)
+    };
+    for commit in older {
+        scan(commit, false)?;
+    }
+    let mut report = scan(newest, true)?;
+    report.timeline = timeline;
+    Ok(report)
+}
+
+/// The commits from `opts.from` to `opts.to` along first parents, oldest
+/// first, thinned out to `opts.every`.
+fn revisions(cfg: &ScanConfig, opts: &TimelineOptions) -> Result<Vec<ScanCommit>, ScanError> {
+    let to = opts.to.as_deref().unwrap_or("HEAD");
+    let err = |rev: &str, e: &dyn fmt::Display| ScanError::Revision(rev.to_string(), e.to_string());
+    let repo = gix::discover(&cfg.root).map_err(|e| err(to, &e))?;
+    let from = resolve_commit(&repo, &opts.from)
+        .map_err(|e| err(&opts.from, &e))?
+        .id;
+    let mut commit = resolve_commit(&repo, to).map_err(|e| err(to, &e))?;
+    let mut chain = Vec::new();
+    loop {
+        chain.push(scan_commit(&commit).map_err(|e| err(to, &e))?);
+        if commit.id == from {
+            break;
+        }
+        let Some(parent) = commit.parent_ids().next() else {
+            return Err(err(
+                &opts.from,
+                &format!("not a first-parent ancestor of {}", to),
+            ));
+        };
+        commit = repo.find_commit(parent.detach()).map_err(|e| err(to, &e))?;
+    }
+    chain.reverse();
+    Ok(pick(chain, opts.every))
+}
+
+/// Keeps the first commit of `chain`, those `every` apart from the previous
+/// pick, and the last one.
+fn pick(chain: Vec<ScanCommit>, every: TimelineStep) -> Vec<ScanCommit> {
+    let last = chain.len().saturating_sub(1);
+    let mut picked: Vec<ScanCommit> = Vec::new();
+    for (i, commit) in chain.into_iter().enumerate() {
+        let due = match (every, picked.last()) {
+            (_, None) => true,
+            (TimelineStep::Commits(n), _) => i % n == 0,
+            (TimelineStep::Days(n), Some(prev)) => {
+                commit.time - prev.time >= n as i64 * SECONDS_PER_DAY
+            }
+        };
+        if due || i == last {
+            picked.push(commit);
+        }
+    }
+    picked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain(days: &[i64]) -> Vec<ScanCommit> {
+        days.iter()
+            .enumerate()
+            .map(|(i, d)| ScanCommit {
+                id: i.to_string(),
+                time: d * SECONDS_PER_DAY,
+            })
+            .collect()
+    }
+
+    fn ids(commits: &[ScanCommit]) -> Vec<&str> {
+        commits.iter().map(|c| c.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_timeline_step_parses_commits_and_days() {
+        assert_eq!("10".parse(), Ok(TimelineStep::Commits(10)));
+        assert_eq!("3 commits".parse(), Ok(TimelineStep::Commits(3)));
+        assert_eq!("1c".parse(), Ok(TimelineStep::Commits(1)));
+        assert_eq!("30d".parse(), Ok(TimelineStep::Days(30)));
+        assert_eq!("7 days".parse(), Ok(TimelineStep::Days(7)));
+        assert!("0".parse::<TimelineStep>().is_err());
+        assert!("2 weeks".parse::<TimelineStep>().is_err());
+        assert!("days".parse::<TimelineStep>().is_err());
+    }
+
+    #[test]
+    fn test_pick_keeps_first_last_and_every_step() {
+        let days = [0, 1, 2, 3, 10, 11];
+        assert_eq!(
+            ids(&pick(chain(&days), TimelineStep::Commits(2))),
+            ["0", "2", "4", "5"]
+        );
+        assert_eq!(
+            ids(&pick(chain(&days), TimelineStep::Days(3))),
+            ["0", "3", "4", "5"]
+        );
+        assert_eq!(ids(&pick(chain(&[5]), TimelineStep::Days(3))), ["0"]);
+    }
+}
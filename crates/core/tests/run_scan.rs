@@ -0,0 +1,488 @@
+#![cfg(feature = "scan")]
+
+use ryoiki_core::{
+    CacheStats, FunctionSpan, Maintainability, ScanConfig, ScanDepth, TimelineOptions,
+    TimelineStep, run_scan, run_timeline,
+};
+use std::fs;
+use std::path::Path;
+
+/// Runs git in `root` with a fixed author and commit date.
+fn git(root: &Path, args: &[&str], date: &str, email: &str) {
+    let status = std::process::Command::new("git")
+        .args(["-c", "user.name=t", "-c", &format!("user.email={}", email)])
+        .args(args)
+        .current_dir(root)
+        .env("GIT_AUTHOR_DATE", date)
+        .env("GIT_COMMITTER_DATE", date)
+        .status()
+        .expect("run git");
+    assert!(status.success(), "git {:?}", args);
+}
+
+#[test]
+fn scan_returns_tree_without_writing_outputs() {
+    let root = std::env::temp_dir().join(format!("ryoiki-run-scan-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(root.join("src")).expect("create fixture dir");
+    fs::write(
+        root.join("src/lib.rs"),
+        "// a\n\nfn a(x: bool) -> u8 {\n    if x { 1 } else { 0 }\n}\n",
+    )
+    .expect("write fixture");
+
+    let report = run_scan(&ScanConfig::new(&root)).expect("scan fixture");
+    let entries: Vec<_> = fs::read_dir(&root)
+        .expect("list fixture")
+        .flatten()
+        .map(|e| e.file_name())
+        .collect();
+    let _ = fs::remove_dir_all(&root);
+
+    assert_eq!(report.tree.kind, "directory");
+    assert_eq!(report.tree.path, ".");
+    assert_eq!(report.tree.metrics.loc, 5);
+    assert_eq!(report.tree.metrics.lines, 5);
+    assert_eq!(report.tree.metrics.code, 3);
+    assert_eq!(report.tree.metrics.comments, 1);
+    assert_eq!(report.tree.metrics.blanks, 1);
+    assert_eq!(report.tree.metrics.functions, 1);
+    assert_eq!(report.tree.language.as_deref(), Some("rust"));
+    assert_eq!(report.totals.files, 1);
+    assert_eq!(entries, vec![std::ffi::OsString::from("src")]);
+}
+
+#[test]
+fn scan_tree_is_sorted_and_repeatable() {
+    let root = std::env::temp_dir().join(format!("ryoiki-run-scan-order-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    for dir in ["b", "a/empty", "c/d"] {
+        fs::create_dir_all(root.join(dir)).expect("create fixture dir");
+    }
+    for file in ["z.rs", "b/x.py", "a.rs", "c/d/y.ts", "c/w.rs"] {
+        fs::write(root.join(file), "fn f() {}\n").expect("write fixture");
+    }
+
+    let first = run_scan(&ScanConfig::new(&root)).expect("scan fixture");
+    let second = run_scan(&ScanConfig::new(&root)).expect("scan fixture");
+    let _ = fs::remove_dir_all(&root);
+
+    let names = |n: &ryoiki_core::Node| -> Vec<String> {
+        n.children
+            .iter()
+            .flatten()
+            .map(|c| c.name.clone())
+            .collect()
+    };
+    assert_eq!(names(&first.tree), ["a", "a.rs", "b", "c", "z.rs"]);
+    let c = &first.tree.children.as_ref().expect("children")[3];
+    assert_eq!(c.path, "c");
+    assert_eq!(names(c), ["d", "w.rs"]);
+    assert_eq!(first.tree.metrics.loc, 5);
+    assert_eq!(
+        serde_json::to_string(&first.tree).expect("serialize"),
+        serde_json::to_string(&second.tree).expect("serialize")
+    );
+}
+
+#[test]
+fn scan_adds_function_nodes_at_function_depth() {
+    let root = std::env::temp_dir().join(format!("ryoiki-run-scan-fns-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(&root).expect("create fixture dir");
+    fs::write(
+        root.join("lib.rs"),
+        "struct S;\nimpl S {\n    fn run(&self, a: u8, b: u8) -> u8 {\n        \
+         if a > b { a } else { b }\n    }\n}\n\
+         fn a() { fn h() {} }\nfn b() { fn h() {} }\ntrait T { fn t(&self); }\n",
+    )
+    .expect("write fixture");
+    fs::write(root.join("notes.txt"), "fn not_code() {}\n").expect("write fixture");
+
+    let files = run_scan(&ScanConfig::new(&root)).expect("scan fixture");
+    let mut cfg = ScanConfig::new(&root);
+    cfg.depth = ScanDepth::Functions;
+    let report = run_scan(&cfg).expect("scan fixture");
+    let _ = fs::remove_dir_all(&root);
+
+    let children = report.tree.children.as_ref().expect("children");
+    assert_eq!(report.tree.metrics.loc, files.tree.metrics.loc);
+    assert_eq!(report.tree.metrics.functions, files.tree.metrics.functions);
+    assert!(
+        files.tree.children.as_ref().expect("children")[0]
+            .children
+            .is_none()
+    );
+    assert!(children[1].children.is_none(), "notes.txt stays a leaf");
+
+    let lib = &children[0];
+    assert_eq!(lib.kind, "file");
+    assert!(lib.entries().is_none());
+    let functions = lib.children.as_ref().expect("function nodes");
+    let names: Vec<_> = functions.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(names, ["S::run", "a", "h:7", "b", "h:8"]);
+    let run = &functions[0];
+    assert_eq!(run.kind, "function");
+    assert_eq!(run.path, "lib.rs#S::run");
+    assert_eq!(run.language.as_deref(), Some("rust"));
+    assert_eq!((run.metrics.loc, run.metrics.complexity), (3, 2));
+    assert_eq!((run.metrics.cognitive, run.metrics.max_nesting), (2, 1));
+
+    let cognitive = &report.advanced.cognitive;
+    assert_eq!(report.tree.metrics.cognitive, 2);
+    assert_eq!(cognitive.total, 2);
+    assert_eq!(cognitive.max_function, 2);
+    assert_eq!(
+        cognitive.max_function_path.as_deref(),
+        Some("lib.rs#S::run")
+    );
+    assert_eq!(cognitive.max_nesting, 1);
+    assert!((cognitive.per_function - 0.4).abs() < 1e-9);
+    let items = &report.advanced.items;
+    assert_eq!(
+        (items.functions, items.methods, items.traits, items.impls),
+        (4, 2, 1, 1)
+    );
+    assert_eq!(
+        run.function,
+        Some(FunctionSpan {
+            line: 3,
+            end_line: 5,
+            params: 2,
+        })
+    );
+}
+
+#[test]
+fn scan_attaches_maintainability_to_files_directories_and_languages() {
+    let root = std::env::temp_dir().join(format!("ryoiki-run-scan-mi-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(root.join("src")).expect("create fixture dir");
+    fs::write(
+        root.join("src/a.rs"),
+        "fn a(x: u8) -> u8 {\n    if x > 1 { x * 2 } else { x + 1 }\n}\n",
+    )
+    .expect("write fixture");
+    fs::write(
+        root.join("src/b.py"),
+        "# b\ndef b(x):\n    if x:\n        return x - 1\n    return 0\n",
+    )
+    .expect("write fixture");
+    fs::write(root.join("README.md"), "# Notes\n").expect("write fixture");
+
+    let report = run_scan(&ScanConfig::new(&root)).expect("scan fixture");
+    let _ = fs::remove_dir_all(&root);
+
+    let children = report.tree.children.as_ref().expect("children");
+    assert!(children[0].maintainability.is_none(), "README.md");
+    let src = &children[1];
+    let files: Vec<_> = src.children.as_ref().expect("children").iter().collect();
+    for file in &files {
+        let m = file.maintainability.as_ref().expect("file maintainability");
+        assert!(m.volume > 0.0 && m.difficulty > 0.0, "{}", file.path);
+        assert!((m.effort - m.volume * m.difficulty).abs() < 1e-9);
+        assert!((0.0..=100.0).contains(&m.index));
+    }
+    let (a, b) = (
+        files[0].maintainability.as_ref().expect("a.rs"),
+        files[1].maintainability.as_ref().expect("b.py"),
+    );
+    let dir = src.maintainability.as_ref().expect("src maintainability");
+    let (code_a, code_b) = (files[0].metrics.code as f64, files[1].metrics.code as f64);
+    assert!((dir.volume - (a.volume + b.volume)).abs() < 1e-9);
+    assert!((dir.effort - (a.effort + b.effort)).abs() < 1e-9);
+    assert!((dir.difficulty - dir.effort / dir.volume).abs() < 1e-9);
+    assert!((dir.index - (a.index * code_a + b.index * code_b) / (code_a + code_b)).abs() < 1e-9);
+    assert_eq!(report.tree.maintainability.as_ref(), Some(dir));
+    assert!((report.advanced.maintainability_index - dir.index).abs() < 1e-9);
+    // Pooling only grows the distinct counts, so both files must be in it.
+    assert!(report.advanced.halstead.volume >= a.volume + b.volume - 1e-9);
+
+    let summary = report.summary();
+    let language = |name: &str| {
+        summary
+            .languages
+            .iter()
+            .find(|l| l.name == name)
+            .unwrap_or_else(|| panic!("{} totals", name))
+    };
+    // One file per language; the difficulty is recomputed from effort and
+    // volume, so compare within rounding.
+    let same = |name: &str, file: &Maintainability| {
+        let m = language(name).maintainability.as_ref().expect("totals");
+        for (x, y) in [
+            (m.volume, file.volume),
+            (m.difficulty, file.difficulty),
+            (m.effort, file.effort),
+            (m.index, file.index),
+        ] {
+            assert!((x - y).abs() < 1e-9, "{}: {:?} vs {:?}", name, m, file);
+        }
+    };
+    same("Rust", a);
+    same("Python", b);
+    assert!(language("Markdown").maintainability.is_none());
+}
+
+#[test]
+fn scan_detects_languages_like_tokei() {
+    let root = std::env::temp_dir().join(format!("ryoiki-run-scan-langs-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(&root).expect("create fixture dir");
+    for (file, body) in [
+        ("Dockerfile", "FROM scratch\n"),
+        ("Makefile", "all:\n\ttrue\n"),
+        ("run", "#!/usr/bin/env python3\nprint(1)\n"),
+        ("a.mts", "export const a = 1\n"),
+    ] {
+        fs::write(root.join(file), body).expect("write fixture");
+    }
+
+    let report = run_scan(&ScanConfig::new(&root)).expect("scan fixture");
+    let _ = fs::remove_dir_all(&root);
+
+    let langs: Vec<(String, Option<String>)> = report
+        .tree
+        .children
+        .iter()
+        .flatten()
+        .map(|c| (c.name.clone(), c.language.clone()))
+        .collect();
+    let expected = [
+        ("Dockerfile", "docker"),
+        ("Makefile", "make"),
+        ("a.mts", "typescript"),
+        ("run", "python"),
+    ];
+    assert_eq!(langs.len(), expected.len());
+    for (got, (name, lang)) in langs.iter().zip(expected) {
+        assert_eq!((got.0.as_str(), got.1.as_deref()), (name, Some(lang)));
+    }
+    assert!(report.languages.iter().any(|l| l.id == "docker"));
+}
+
+#[test]
+fn scan_applies_globs_hidden_and_size_limits() {
+    let root = std::env::temp_dir().join(format!("ryoiki-run-scan-globs-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    for dir in ["src/gen", ".github", "docs"] {
+        fs::create_dir_all(root.join(dir)).expect("create fixture dir");
+    }
+    for (file, body) in [
+        ("src/a.rs", "fn a() {}\n".to_string()),
+        ("src/big.rs", "fn b() {}\n".repeat(100)),
+        ("src/gen/g.rs", "fn g() {}\n".to_string()),
+        (".github/ci.yml", "on: push\n".to_string()),
+        ("docs/d.md", "# d\n".to_string()),
+    ] {
+        fs::write(root.join(file), body).expect("write fixture");
+    }
+    let paths = |cfg: &ScanConfig| -> Vec<String> {
+        fn collect(n: &ryoiki_core::Node, out: &mut Vec<String>) {
+            match &n.children {
+                Some(c) => c.iter().for_each(|c| collect(c, out)),
+                None => out.push(n.path.replace('\\', "/")),
+            }
+        }
+        let mut out = Vec::new();
+        collect(&run_scan(cfg).expect("scan fixture").tree, &mut out);
+        out
+    };
+
+    let mut cfg = ScanConfig::new(&root);
+    assert_eq!(
+        paths(&cfg),
+        ["docs/d.md", "src/a.rs", "src/big.rs", "src/gen/g.rs"]
+    );
+
+    cfg.includes = vec!["*.rs".to_string(), "*.yml".to_string()];
+    cfg.excludes.push("src/gen".to_string());
+    cfg.hidden = true;
+    cfg.max_file_size = Some(100);
+    let scanned = paths(&cfg);
+
+    cfg.excludes.push("[".to_string());
+    let invalid = run_scan(&cfg);
+    let _ = fs::remove_dir_all(&root);
+
+    assert_eq!(scanned, [".github/ci.yml", "src/a.rs"]);
+    assert!(invalid.is_err());
+}
+
+#[test]
+fn scan_attaches_git_history() {
+    let root = std::env::temp_dir().join(format!("ryoiki-run-scan-git-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(root.join("src")).expect("create fixture dir");
+    let git = |args: &[&str], date: &str, email: &str| git(&root, args, date, email);
+    git(&["init", "-q"], "2024-01-01T00:00:00Z", "a@x");
+    fs::write(root.join("src/a.rs"), "fn a() {}\n").expect("write fixture");
+    fs::write(root.join("top.rs"), "fn t() {}\n").expect("write fixture");
+    git(&["add", "."], "2024-01-01T00:00:00Z", "a@x");
+    git(&["commit", "-qm", "one"], "2024-01-01T00:00:00Z", "a@x");
+    fs::write(root.join("src/a.rs"), "fn a() {}\nfn b() {}\n").expect("write fixture");
+    fs::write(root.join("src/new.rs"), "fn n() {}\n").expect("write fixture");
+    git(&["add", "."], "2024-01-31T00:00:00Z", "b@x");
+    git(&["commit", "-qm", "two"], "2024-01-31T00:00:00Z", "B@x");
+
+    let mut cfg = ScanConfig::new(root.join("src"));
+    let all = run_scan(&cfg).expect("scan fixture");
+    cfg.history = Some(ryoiki_core::HistoryOptions {
+        since_days: Some(10),
+    });
+    let recent = run_scan(&cfg).expect("scan fixture");
+    cfg.coupling = Some(ryoiki_core::CouplingOptions {
+        min_support: 1,
+        min_degree: 50.0,
+        max_changeset_size: 2,
+    });
+    let coupled = run_scan(&cfg).expect("scan fixture");
+    cfg.history = None;
+    let without = run_scan(&cfg).expect("scan fixture");
+    let _ = fs::remove_dir_all(&root);
+
+    let git_of = |r: &ryoiki_core::ScanReport, i: usize| {
+        r.tree.children.as_ref().expect("children")[i]
+            .git
+            .clone()
+            .expect("history")
+    };
+    let a = git_of(&all, 0);
+    assert_eq!(
+        (a.commits, a.lines_added, a.lines_deleted, a.authors),
+        (2, 2, 0, 2)
+    );
+    assert_eq!((a.first_commit, a.last_commit), (Some(30), Some(0)));
+    let root_git = all.tree.git.as_ref().expect("history");
+    assert_eq!((root_git.commits, root_git.lines_added), (2, 3));
+    assert_eq!(git_of(&recent, 0).commits, 1);
+    assert_eq!(git_of(&recent, 0).first_commit, Some(0));
+    assert!(without.tree.git.is_none());
+
+    let hot: Vec<&str> = all.hotspots.iter().map(|h| h.path.as_str()).collect();
+    assert_eq!(hot, ["a.rs", "new.rs"]);
+    assert_eq!(all.tree.hotspot_score, Some(1.0));
+    assert!(without.hotspots.is_empty());
+
+    // One shared commit is below the default support threshold.
+    assert!(all.tree.edges.is_empty());
+    let edges: Vec<(&str, &str, usize)> = coupled
+        .tree
+        .edges
+        .iter()
+        .map(|e| (e.from.as_str(), e.to.as_str(), e.support))
+        .collect();
+    assert_eq!(edges, [("a.rs", "new.rs", 1)]);
+    assert_eq!(coupled.tree.edges[0].confidence, 1.0);
+    assert!(without.tree.hotspot_score.is_none());
+}
+
+#[test]
+fn scan_reads_revision_from_object_database() {
+    let root = std::env::temp_dir().join(format!("ryoiki-run-scan-rev-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(root.join("src/vendor")).expect("create fixture dir");
+    let git = |args: &[&str]| git(&root, args, "2024-01-01T00:00:00Z", "a@x");
+    git(&["init", "-q"]);
+    fs::write(root.join("src/a.rs"), "fn a() {}\n").expect("write fixture");
+    fs::write(root.join("src/.gitignore"), "gen.rs\n").expect("write fixture");
+    fs::write(root.join("src/gen.rs"), "fn g() {}\n").expect("write fixture");
+    fs::write(root.join("src/vendor/v.rs"), "fn v() {}\n").expect("write fixture");
+    fs::write(root.join("src/tool"), "#!/bin/sh\necho hi\n").expect("write fixture");
+    fs::write(root.join("src/local.rs"), "fn l() {}\n").expect("write fixture");
+    git(&["add", "-A"]);
+    git(&["add", "-f", "src/gen.rs"]);
+    fs::write(root.join(".git/info/exclude"), "local.rs\n").expect("write fixture");
+    git(&["commit", "-qm", "one"]);
+    git(&["tag", "v1"]);
+    fs::write(root.join("src/a.rs"), "fn a() {}\nfn b() {}\n").expect("write fixture");
+    fs::write(root.join("src/b.rs"), "fn b() {}\n").expect("write fixture");
+    git(&["add", "-A"]);
+    git(&["commit", "-qm", "two"]);
+
+    let mut cfg = ScanConfig::new(root.join("src"));
+    cfg.excludes.push("vendor".to_string());
+    let worktree = run_scan(&cfg).expect("scan working tree");
+    cfg.revision = Some("HEAD".to_string());
+    let head = run_scan(&cfg).expect("scan HEAD");
+    fs::write(root.join("src/dirty.rs"), "fn d() {}\n").expect("write fixture");
+    fs::remove_file(root.join("src/a.rs")).expect("remove fixture");
+    fs::write(root.join("src/tool"), "#!/usr/bin/env python3\nprint(1)\n").expect("write fixture");
+    cfg.revision = Some("v1".to_string());
+    let v1 = run_scan(&cfg).expect("scan tag");
+    cfg.revision = Some("no-such-tag".to_string());
+    let missing = run_scan(&cfg);
+    let _ = fs::remove_dir_all(&root);
+
+    assert_eq!(
+        serde_json::to_value(&head.tree).expect("serialize"),
+        serde_json::to_value(&worktree.tree).expect("serialize")
+    );
+    assert_eq!(worktree.commit, head.commit);
+    assert_eq!(head.commit.as_ref().map(|c| c.id.len()), Some(40));
+
+    // Ignored, excluded and uncommitted files are skipped, also those only
+    // listed in .git/info/exclude; edits after the tag are not seen, and
+    // the script's language comes from the committed shebang.
+    let children = v1.tree.children.as_ref().expect("children");
+    let names: Vec<&str> = children.iter().map(|n| n.name.as_str()).collect();
+    assert_eq!(names, ["a.rs", "tool"]);
+    assert_eq!(children[1].language.as_deref(), Some("shell"));
+    assert_eq!(v1.tree.metrics.functions, 1);
+    assert_eq!(v1.tree.git.as_ref().map(|g| g.commits), Some(1));
+    assert_ne!(v1.commit, head.commit);
+    assert!(missing.is_err());
+}
+
+#[test]
+fn timeline_scans_revisions_and_reuses_unchanged_blobs() {
+    let root =
+        std::env::temp_dir().join(format!("ryoiki-run-scan-timeline-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(&root).expect("create fixture dir");
+    git(&root, &["init", "-q"], "2024-01-01T00:00:00Z", "a@x");
+    let commits = [
+        ("a.rs", "fn a() {}\n", "2024-01-01T00:00:00Z"),
+        ("b.rs", "fn b() {}\n", "2024-01-02T00:00:00Z"),
+        ("b.rs", "fn b() {}\nfn c() {}\n", "2024-01-03T00:00:00Z"),
+        ("c.rs", "fn c() {}\n", "2024-01-09T00:00:00Z"),
+    ];
+    for (file, body, date) in commits {
+        fs::write(root.join(file), body).expect("write fixture");
+        git(&root, &["add", "-A"], date, "a@x");
+        git(&root, &["commit", "-qm", file], date, "a@x");
+    }
+    fs::write(root.join("dirty.rs"), "fn d() {}\n").expect("write fixture");
+
+    let cfg = ScanConfig::new(&root);
+    let mut opts = TimelineOptions {
+        from: "HEAD~3".to_string(),
+        to: None,
+        every: TimelineStep::Commits(2),
+    };
+    let mut stats = Vec::new();
+    let report = run_timeline(&cfg, &opts, |r| stats.push(r.cache.clone())).expect("timeline");
+    opts.every = TimelineStep::Days(2);
+    let by_days = run_timeline(&cfg, &opts, |_| {}).expect("timeline");
+    opts.from = "no-such-tag".to_string();
+    let missing = run_timeline(&cfg, &opts, |_| {});
+    let _ = fs::remove_dir_all(&root);
+
+    let files: Vec<usize> = report.timeline.iter().map(|p| p.totals.files).collect();
+    assert_eq!(files, [1, 2, 3]);
+    assert_eq!(report.totals.files, 3);
+    assert_eq!(
+        report.commit.as_ref(),
+        report.timeline.last().map(|p| &p.commit)
+    );
+    let hits = |hits, misses| Some(CacheStats { hits, misses });
+    assert_eq!(stats, [hits(0, 1), hits(1, 1), hits(2, 1)]);
+
+    let days: Vec<i64> = by_days
+        .timeline
+        .iter()
+        .map(|p| (p.commit.time - 1_704_067_200) / 86_400)
+        .collect();
+    assert_eq!(days, [0, 2, 8]);
+    assert!(missing.is_err());
+}
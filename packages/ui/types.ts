@@ -2,15 +2,74 @@ export interface Metrics {
   loc: number;
   complexity?: number;
   functions?: number;
+  code?: number;
+  comments?: number;
+  blanks?: number;
+}
+
+export interface GitMetrics {
+  commits: number;
+  lines_added: number;
+  lines_deleted: number;
+  authors: number;
+  first_commit?: number;
+  last_commit?: number;
+}
+
+export interface CouplingEdge {
+  from: string;
+  to: string;
+  support: number;
+  confidence: number;
+  degree: number;
+}
+
+export type DeltaStatus = 'unchanged' | 'changed' | 'added' | 'removed' | 'moved';
+
+export interface MetricsDelta {
+  loc: number;
+  complexity: number;
+  functions: number;
+  code: number;
+  lines: number;
+  comments: number;
+  blanks: number;
+}
+
+export interface NodeDelta {
+  status: DeltaStatus;
+  moved_from?: string;
+  before: Metrics;
+  delta: MetricsDelta;
+}
+
+export interface FunctionSpan {
+  line: number;
+  end_line: number;
+  params: number;
+}
+
+export interface Maintainability {
+  volume: number;
+  difficulty: number;
+  effort: number;
+  index: number;
 }
 
 export interface CodeTree {
   name: string;
   path: string;
-  kind: 'file' | 'directory';
+  kind: 'file' | 'directory' | 'function';
   metrics: Metrics;
   language: string;
   children?: CodeTree[];
+  attributes?: Record<string, number>;
+  git?: GitMetrics;
+  hotspot_score?: number;
+  edges?: CouplingEdge[];
+  delta?: NodeDelta;
+  function?: FunctionSpan;
+  maintainability?: Maintainability;
 }
 
 export interface RectNode {
@@ -22,6 +81,12 @@ export interface RectNode {
   depth: number;
   metrics: Metrics;
   language: string;
+  attributes?: Record<string, number>;
+  git?: GitMetrics;
+  hotspot_score?: number;
+  delta?: NodeDelta;
+  function?: FunctionSpan;
+  maintainability?: Maintainability;
 }
 
 export interface BuildingData {
@@ -0,0 +1,338 @@
+use clap::ValueEnum;
+use ryoiki_core::{
+    AdvancedMetrics, CacheStats, ConfigOverrides, Diagnostic, Node, ScanCommit, ScanConfig,
+    ScanDepth, ScanReport, SnapshotStore, TimelineOptions, TimelinePoint, TimelineStep, Totals,
+    delta_tree, diff_trees, parse_tree, run_scan, run_timeline, to_codecharta, write_outputs,
+};
+use serde::Serialize;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The command succeeded (for `diff`: both sides are identical).
+pub const EXIT_OK: u8 = 0;
+/// Scan, I/O or serialization failure. Also used by `diff` for "differences found".
+pub const EXIT_FAILURE: u8 = 1;
+/// `diff` could not load one of its inputs.
+pub const EXIT_DIFF_ERROR: u8 = 2;
+
+type CmdResult<T> = Result<T, Box<dyn std::error::Error>>;
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum ExportFormat {
+    /// The `ryoiki.cc.json` tree.
+    Tree,
+    /// The `ryoiki.metrics.json` summary.
+    Metrics,
+    /// The full scan report, including per-language totals and diagnostics.
+    Report,
+    /// One CSV row per file.
+    Csv,
+    /// CodeCharta's own cc.json format.
+    Codecharta,
+    /// The files ranked by hotspot score (complexity times change frequency).
+    Hotspots,
+}
+
+#[derive(Serialize)]
+struct ScanOutput<'a> {
+    root: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    commit: Option<&'a ScanCommit>,
+    outputs: Vec<String>,
+    totals: &'a Totals,
+    advanced: &'a AdvancedMetrics,
+    diagnostics: &'a [Diagnostic],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cache: Option<&'a CacheStats>,
+}
+
+fn project_root() -> CmdResult<PathBuf> {
+    Ok(std::env::current_dir()?)
+}
+
+fn print_json<T: Serialize>(value: &T) -> CmdResult<()> {
+    let json = serde_json::to_string_pretty(value)?;
+    writeln!(std::io::stdout(), "{}", json)?;
+    Ok(())
+}
+
+fn exit_on_error(result: CmdResult<()>) -> ExitCode {
+    match result {
+        Ok(()) => ExitCode::from(EXIT_OK),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::from(EXIT_FAILURE)
+        }
+    }
+}
+
+pub fn scan(
+    dir: Option<&Path>,
+    out: Option<&Path>,
+    no_cache: bool,
+    rev: Option<String>,
+    depth: Option<ScanDepth>,
+) -> ExitCode {
+    exit_on_error(scan_inner(dir, out, no_cache, rev, depth))
+}
+
+/// The configuration of `scan` and `timeline`: `out` replaces the
+/// configured output directories and moves the cache there.
+fn scan_config(dir: Option<&Path>, out: Option<&Path>, no_cache: bool) -> CmdResult<ScanConfig> {
+    let mut cfg = ScanConfig::from_tools_config(&project_root()?, &ConfigOverrides::for_dir(dir))?;
+    if let Some(out) = out {
+        cfg.outputs.metrics_dir = Some(out.to_path_buf());
+        cfg.outputs.web_dir = None;
+        cfg.cache_path = Some(out.join("ryoiki.cache.json"));
+    }
+    if no_cache {
+        cfg.cache_path = None;
+    }
+    Ok(cfg)
+}
+
+fn output_dirs(cfg: &ScanConfig) -> Vec<String> {
+    [&cfg.outputs.metrics_dir, &cfg.outputs.web_dir]
+        .into_iter()
+        .flatten()
+        .map(|p| p.to_string_lossy().to_string())
+        .collect()
+}
+
+fn scan_inner(
+    dir: Option<&Path>,
+    out: Option<&Path>,
+    no_cache: bool,
+    rev: Option<String>,
+    depth: Option<ScanDepth>,
+) -> CmdResult<()> {
+    let mut cfg = scan_config(dir, out, no_cache)?;
+    cfg.revision = rev;
+    if let Some(depth) = depth {
+        cfg.depth = depth;
+    }
+    let report = run_scan(&cfg)?;
+    write_outputs(&report, &cfg.outputs)?;
+    save_snapshot(&report, &cfg);
+    print_json(&ScanOutput {
+        root: cfg.root.to_string_lossy().to_string(),
+        commit: report.commit.as_ref(),
+        outputs: output_dirs(&cfg),
+        totals: &report.totals,
+        advanced: &report.advanced,
+        diagnostics: &report.diagnostics,
+        cache: report.cache.as_ref(),
+    })
+}
+
+#[derive(Serialize)]
+struct TimelineOutput<'a> {
+    root: String,
+    outputs: Vec<String>,
+    timeline: &'a [TimelinePoint],
+}
+
+pub struct TimelineArgs<'a> {
+    pub dir: Option<&'a Path>,
+    pub out: Option<&'a Path>,
+    pub no_cache: bool,
+    pub from: String,
+    pub to: Option<String>,
+    pub every: TimelineStep,
+    pub trees: bool,
+}
+
+pub fn timeline(args: TimelineArgs<'_>) -> ExitCode {
+    exit_on_error(timeline_inner(args))
+}
+
+/// Scans past revisions and writes the newest one as `scan` would, with the
+/// summary metrics of all of them in `ryoiki.metrics.json`. With `trees`,
+/// every revision is also stored as a snapshot dated by its commit.
+fn timeline_inner(args: TimelineArgs<'_>) -> CmdResult<()> {
+    let cfg = scan_config(args.dir, args.out, args.no_cache)?;
+    let store = match (&cfg.outputs.metrics_dir, args.trees) {
+        (Some(dir), true) => Some(SnapshotStore::new(dir)),
+        (None, true) => return Err("--trees needs a metrics directory".into()),
+        (_, false) => None,
+    };
+    let snapshot_opts = cfg.snapshots.clone().unwrap_or_default();
+    let opts = TimelineOptions {
+        from: args.from,
+        to: args.to,
+        every: args.every,
+    };
+    let mut saved = Ok(());
+    let report = run_timeline(&cfg, &opts, |report| {
+        let (Some(store), Some(commit)) = (&store, &report.commit) else {
+            return;
+        };
+        if saved.is_ok() {
+            let time = u64::try_from(commit.time).unwrap_or(0);
+            saved = store.save(report, time, &snapshot_opts).map(drop);
+        }
+    })?;
+    saved.map_err(|e| format!("snapshot not saved: {}", e))?;
+    write_outputs(&report, &cfg.outputs)?;
+    print_json(&TimelineOutput {
+        root: cfg.root.to_string_lossy().to_string(),
+        outputs: output_dirs(&cfg),
+        timeline: &report.timeline,
+    })
+}
+
+/// Adds `report` to the snapshot history in the metrics directory, when
+/// configured. A failure is reported but does not fail the scan. A scan of
+/// a past revision is filed at its commit time, like `timeline --trees`
+/// does; a working-tree scan at the current time.
+pub fn save_snapshot(report: &ScanReport, cfg: &ScanConfig) {
+    let (Some(opts), Some(dir)) = (&cfg.snapshots, &cfg.outputs.metrics_dir) else {
+        return;
+    };
+    let time = match (&cfg.revision, &report.commit) {
+        (Some(_), Some(commit)) => u64::try_from(commit.time).unwrap_or(0),
+        _ => SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs()),
+    };
+    if let Err(e) = SnapshotStore::new(dir).save(report, time, opts) {
+        eprintln!("warning: snapshot not saved: {}", e);
+    }
+}
+
+pub fn config(dir: Option<&Path>) -> ExitCode {
+    exit_on_error(config_inner(dir))
+}
+
+/// Prints the configuration a `scan` of `dir` would use, after all layers
+/// have been applied.
+fn config_inner(dir: Option<&Path>) -> CmdResult<()> {
+    let cfg = ScanConfig::from_tools_config(&project_root()?, &ConfigOverrides::for_dir(dir))?;
+    print_json(&cfg)
+}
+
+pub fn export(dir: Option<&Path>, format: ExportFormat, output: Option<&Path>) -> ExitCode {
+    exit_on_error(export_inner(dir, format, output))
+}
+
+fn export_inner(dir: Option<&Path>, format: ExportFormat, output: Option<&Path>) -> CmdResult<()> {
+    let mut cfg = ScanConfig::from_tools_config(&project_root()?, &ConfigOverrides::for_dir(dir))?;
+    // Exporting only reads; it leaves the scan cache alone.
+    cfg.cache_path = None;
+    let report = run_scan(&cfg)?;
+    let body = render_export(&report, format)?;
+    match output {
+        Some(path) => fs::write(path, body)?,
+        None => std::io::stdout().write_all(body.as_bytes())?,
+    }
+    Ok(())
+}
+
+fn render_export(report: &ScanReport, format: ExportFormat) -> CmdResult<String> {
+    Ok(match format {
+        ExportFormat::Tree => serde_json::to_string_pretty(&report.tree)? + "\n",
+        ExportFormat::Metrics => serde_json::to_string_pretty(&report.summary())? + "\n",
+        ExportFormat::Report => serde_json::to_string_pretty(report)? + "\n",
+        ExportFormat::Csv => {
+            let mut out = String::from(
+                "path,language,loc,code,comments,blanks,complexity,functions,cognitive,max_nesting,\
+                 maintainability_index\n",
+            );
+            write_csv_rows(&report.tree, &mut out);
+            out
+        }
+        ExportFormat::Codecharta => {
+            let cc = to_codecharta(&report.tree, &report.tree.name);
+            serde_json::to_string_pretty(&cc)? + "\n"
+        }
+        ExportFormat::Hotspots => serde_json::to_string_pretty(&report.hotspots)? + "\n",
+    })
+}
+
+fn write_csv_rows(node: &Node, out: &mut String) {
+    match node.entries() {
+        Some(children) => {
+            for c in children {
+                write_csv_rows(c, out);
+            }
+        }
+        None => {
+            out.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{},{},{}\n",
+                csv_field(&node.path),
+                csv_field(node.language.as_deref().unwrap_or("")),
+                node.metrics.loc,
+                node.metrics.code,
+                node.metrics.comments,
+                node.metrics.blanks,
+                node.metrics.complexity,
+                node.metrics.functions,
+                node.metrics.cognitive,
+                node.metrics.max_nesting,
+                node.maintainability
+                    .as_ref()
+                    .map_or(String::new(), |m| format!("{:.2}", m.index))
+            ));
+        }
+    }
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+pub fn diff(a: &Path, b: &Path) -> ExitCode {
+    let loaded = load_tree(a).and_then(|ta| Ok((ta, load_tree(b)?)));
+    let (before, after) = match loaded {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return ExitCode::from(EXIT_DIFF_ERROR);
+        }
+    };
+    let d = diff_trees(&before, &after);
+    if let Err(e) = print_json(&d) {
+        eprintln!("error: {}", e);
+        return ExitCode::from(EXIT_DIFF_ERROR);
+    }
+    if d.is_empty() {
+        ExitCode::from(EXIT_OK)
+    } else {
+        ExitCode::from(EXIT_FAILURE)
+    }
+}
+
+pub fn delta(a: &Path, b: &Path, output: Option<&Path>) -> ExitCode {
+    exit_on_error(delta_inner(a, b, output))
+}
+
+fn delta_inner(a: &Path, b: &Path, output: Option<&Path>) -> CmdResult<()> {
+    let tree = delta_tree(&load_tree(a)?, &load_tree(b)?);
+    let body = serde_json::to_string_pretty(&tree)? + "\n";
+    match output {
+        Some(path) => fs::write(path, body)?,
+        None => std::io::stdout().write_all(body.as_bytes())?,
+    }
+    Ok(())
+}
+
+/// A directory is scanned; anything else is read as a `ryoiki.cc.json` or
+/// CodeCharta `cc.json` / `cc.json.gz` file.
+fn load_tree(path: &Path) -> CmdResult<Node> {
+    if path.is_dir() {
+        let mut cfg =
+            ScanConfig::from_tools_config(&project_root()?, &ConfigOverrides::for_dir(Some(path)))?;
+        cfg.cache_path = None;
+        return Ok(run_scan(&cfg)?.tree);
+    }
+    let bytes = fs::read(path).map_err(|e| format!("read {} failed: {}", path.display(), e))?;
+    let tree = parse_tree(&bytes).map_err(|e| format!("parse {} failed: {}", path.display(), e))?;
+    Ok(tree)
+}
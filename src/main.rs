@@ -1,63 +1,166 @@
-pub mod metrics_calc;
-pub mod scan;
+mod commands;
+mod server;
 
-use axum::{
-    Router,
-    http::StatusCode,
-    response::{IntoResponse, Json},
-    routing::post,
-};
+use clap::{Parser, Subcommand};
+use ryoiki_core::{ScanDepth, TimelineStep};
 use std::net::SocketAddr;
-use tower_http::cors::CorsLayer;
-use tower_http::services::ServeDir;
+use std::path::PathBuf;
+use std::process::ExitCode;
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let rt = tokio::runtime::Builder::new_multi_thread()
-        .enable_all()
-        .build()?;
-    rt.block_on(async_main())
+/// Code metrics scanner and 3D treemap server.
+///
+/// Exit codes: 0 on success, 1 on failure, 2 on usage errors. `diff` exits
+/// 0 when the trees match, 1 when they differ and 2 when an input could not
+/// be loaded.
+#[derive(Parser)]
+#[command(name = "ryoiki", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-async fn async_main() -> Result<(), Box<dyn std::error::Error>> {
-    // Serve static files from apps/web/public/ryoiki.cc.json if requested directly?
-    // Vite handles static files in dev.
-    // In prod, we might want to serve .
-
-    let app = Router::new()
-        .route("/api/refresh", post(refresh_handler))
-        .fallback_service(ServeDir::new("apps/web/dist"))
-        .layer(CorsLayer::permissive());
-
-    let addr = SocketAddr::from(([127, 0, 0, 1], 3030));
-    println!("Ryoiki server listening on {}", addr);
+#[derive(Subcommand)]
+enum Command {
+    /// Scan a directory, write ryoiki.cc.json / ryoiki.metrics.json and print
+    /// a JSON summary to stdout.
+    Scan {
+        /// Directory to scan. Defaults to the audit_dir from tools.config.json.
+        dir: Option<PathBuf>,
+        /// Directory to write the outputs to instead of the configured metrics_dir.
+        #[arg(long)]
+        out: Option<PathBuf>,
+        /// Re-analyze every file instead of reusing ryoiki.cache.json.
+        #[arg(long)]
+        no_cache: bool,
+        /// Scan this commit, tag or branch from the git object database
+        /// instead of the working tree.
+        #[arg(long)]
+        rev: Option<String>,
+        /// `functions` adds a node per function and method under each file;
+        /// overrides `scan.depth` from tools.config.json.
+        #[arg(long)]
+        depth: Option<ScanDepth>,
+    },
+    /// Scan past revisions of the git repository, oldest first, and write the
+    /// newest one as `scan` would, with the summary metrics of every revision
+    /// in the `timeline` of ryoiki.metrics.json.
+    Timeline {
+        /// Directory to scan. Defaults to the audit_dir from tools.config.json.
+        dir: Option<PathBuf>,
+        /// The oldest revision to scan, a first-parent ancestor of --to.
+        #[arg(long)]
+        from: String,
+        /// The newest revision to scan.
+        #[arg(long, default_value = "HEAD")]
+        to: String,
+        /// Distance between scanned revisions: `<n>` or `<n> commits`, or
+        /// `<n> days`. The first and last revision are always scanned.
+        #[arg(long, default_value = "1")]
+        every: TimelineStep,
+        /// Directory to write the outputs to instead of the configured metrics_dir.
+        #[arg(long)]
+        out: Option<PathBuf>,
+        /// Start from an empty cache instead of ryoiki.cache.json.
+        #[arg(long)]
+        no_cache: bool,
+        /// Also store the full tree of every revision in the snapshot history.
+        #[arg(long)]
+        trees: bool,
+    },
+    /// Start the HTTP server (the default when no subcommand is given).
+    Serve {
+        #[arg(long, default_value_t = 3030)]
+        port: u16,
+        /// Directory to scan on refresh. Defaults to the configured audit_dir.
+        #[arg(long)]
+        root: Option<PathBuf>,
+    },
+    /// Scan a directory and print the result in the requested format.
+    Export {
+        /// Directory to scan. Defaults to the audit_dir from tools.config.json.
+        dir: Option<PathBuf>,
+        #[arg(long, value_enum, default_value = "tree")]
+        format: commands::ExportFormat,
+        /// Write to this file instead of stdout.
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Print the effective configuration as JSON, after applying the command
+    /// line, environment, tools.config.toml / tools.config.json and defaults.
+    Config {
+        /// Directory to scan. Defaults to the configured audit_dir.
+        dir: Option<PathBuf>,
+    },
+    /// Compare two scans. Each side is a directory to scan or a ryoiki.cc.json file.
+    Diff { a: PathBuf, b: PathBuf },
+    /// Print the delta tree of two scans: before, after and delta metrics for
+    /// every node, with files marked added, removed or moved.
+    Delta {
+        a: PathBuf,
+        b: PathBuf,
+        /// Write to this file instead of stdout.
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+}
 
-    let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
-    Ok(())
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match cli.command {
+        Some(Command::Scan {
+            dir,
+            out,
+            no_cache,
+            rev,
+            depth,
+        }) => commands::scan(dir.as_deref(), out.as_deref(), no_cache, rev, depth),
+        Some(Command::Timeline {
+            dir,
+            from,
+            to,
+            every,
+            out,
+            no_cache,
+            trees,
+        }) => commands::timeline(commands::TimelineArgs {
+            dir: dir.as_deref(),
+            out: out.as_deref(),
+            no_cache,
+            from,
+            to: Some(to),
+            every,
+            trees,
+        }),
+        Some(Command::Export {
+            dir,
+            format,
+            output,
+        }) => commands::export(dir.as_deref(), format, output.as_deref()),
+        Some(Command::Config { dir }) => commands::config(dir.as_deref()),
+        Some(Command::Diff { a, b }) => commands::diff(&a, &b),
+        Some(Command::Delta { a, b, output }) => commands::delta(&a, &b, output.as_deref()),
+        Some(Command::Serve { port, root }) => run_server(port, root),
+        None => run_server(3030, None),
+    }
 }
 
-async fn refresh_handler() -> impl IntoResponse {
-    println!("Refresh requested");
-    match scan::run_scan() {
-        Ok(json) => {
-            println!("Scan completed successfully");
-            // Return the JSON object
-            match serde_json::from_str::<serde_json::Value>(&json) {
-                Ok(v) => (StatusCode::OK, Json(v)),
-                Err(e) => (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    #[allow(clippy::disallowed_methods)]
-                    Json(serde_json::json!({ "error": format!("Invalid JSON generated: {}", e) })),
-                ),
-            }
-        }
+fn run_server(port: u16, root: Option<PathBuf>) -> ExitCode {
+    let result = (|| -> Result<(), Box<dyn std::error::Error>> {
+        let target = server::ServeTarget {
+            project_root: std::env::current_dir()?,
+            root,
+        };
+        let rt = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()?;
+        let addr = SocketAddr::from(([127, 0, 0, 1], port));
+        rt.block_on(server::serve(addr, target))
+    })();
+    match result {
+        Ok(()) => ExitCode::from(commands::EXIT_OK),
         Err(e) => {
-            eprintln!("Scan failed: {}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                #[allow(clippy::disallowed_methods)]
-                Json(serde_json::json!({ "error": e.to_string() })),
-            )
+            eprintln!("error: {}", e);
+            ExitCode::from(commands::EXIT_FAILURE)
         }
     }
 }
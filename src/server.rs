@@ -0,0 +1,262 @@
+use axum::{
+    Router,
+    body::Bytes,
+    extract::{DefaultBodyLimit, Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+    routing::{get, post},
+};
+use ryoiki_core::{
+    ConfigOverrides, ScanConfig, ScanError, SeriesPoint, SnapshotStore, merge_trees, parse_tree,
+    run_scan, write_outputs,
+};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tower_http::cors::CorsLayer;
+use tower_http::services::ServeDir;
+
+/// Where `/api/refresh` scans. The config file is re-read on every refresh so
+/// edits take effect without a restart.
+pub struct ServeTarget {
+    pub project_root: PathBuf,
+    pub root: Option<PathBuf>,
+}
+
+pub async fn serve(
+    addr: SocketAddr,
+    target: ServeTarget,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Vite serves apps/web/public in dev; in prod the built UI lives in dist.
+    let app = Router::new()
+        .route("/api/refresh", post(refresh_handler))
+        .route("/api/history", get(history_handler))
+        .route("/api/history/:id", get(snapshot_handler))
+        .route("/api/history/series/:metric", get(series_handler))
+        .route(
+            "/api/import",
+            post(import_handler).layer(DefaultBodyLimit::max(IMPORT_BODY_LIMIT)),
+        )
+        .fallback_service(ServeDir::new("apps/web/dist"))
+        .layer(CorsLayer::permissive())
+        .with_state(Arc::new(target));
+
+    println!("Ryoiki server listening on {}", addr);
+
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+async fn refresh_handler(State(target): State<Arc<ServeTarget>>) -> impl IntoResponse {
+    println!("Refresh requested");
+    let cfg = match ScanConfig::from_tools_config(
+        &target.project_root,
+        &ConfigOverrides::for_dir(target.root.as_deref()),
+    ) {
+        Ok(cfg) => cfg,
+        Err(e) => {
+            eprintln!("Invalid configuration: {}", e);
+            return error_response(e.to_string());
+        }
+    };
+    // Scanning and writing the outputs block for a while; keep them off the
+    // runtime's worker threads.
+    let scanned = tokio::task::spawn_blocking(move || {
+        let report = run_scan(&cfg)?;
+        println!("Scan completed successfully");
+        if let Err(e) = write_outputs(&report, &cfg.outputs) {
+            eprintln!("Writing scan outputs failed: {}", e);
+        }
+        crate::commands::save_snapshot(&report, &cfg);
+        Ok::<_, ScanError>(report.tree)
+    })
+    .await;
+    match scanned {
+        Ok(Ok(tree)) => match serde_json::to_value(&tree) {
+            Ok(v) => (StatusCode::OK, Json(v)),
+            Err(e) => error_response(format!("Invalid JSON generated: {}", e)),
+        },
+        Ok(Err(e)) => {
+            eprintln!("Scan failed: {}", e);
+            error_response(e.to_string())
+        }
+        Err(e) => {
+            eprintln!("Scan task failed: {}", e);
+            error_response(e.to_string())
+        }
+    }
+}
+
+type ApiResponse = (StatusCode, Json<serde_json::Value>);
+
+/// The snapshot store of the configured metrics directory.
+fn snapshot_store(target: &ServeTarget) -> Result<SnapshotStore, ApiResponse> {
+    let cfg = ScanConfig::from_tools_config(
+        &target.project_root,
+        &ConfigOverrides::for_dir(target.root.as_deref()),
+    )
+    .map_err(|e| error_response(e.to_string()))?;
+    match &cfg.outputs.metrics_dir {
+        Some(dir) => Ok(SnapshotStore::new(dir)),
+        None => Err(error_response(
+            "no metrics directory configured".to_string(),
+        )),
+    }
+}
+
+/// Runs `read` against the snapshot store on the blocking pool; reading and
+/// parsing snapshot files would otherwise stall the runtime's workers.
+async fn read_snapshots<F>(target: Arc<ServeTarget>, read: F) -> ApiResponse
+where
+    F: FnOnce(SnapshotStore) -> ApiResponse + Send + 'static,
+{
+    let response = tokio::task::spawn_blocking(move || match snapshot_store(&target) {
+        Ok(store) => read(store),
+        Err(resp) => resp,
+    })
+    .await;
+    response.unwrap_or_else(|e| error_response(format!("Snapshot task failed: {}", e)))
+}
+
+fn json_response<T: Serialize>(value: &T) -> ApiResponse {
+    match serde_json::to_value(value) {
+        Ok(v) => (StatusCode::OK, Json(v)),
+        Err(e) => error_response(format!("Invalid JSON generated: {}", e)),
+    }
+}
+
+/// Every stored snapshot without its tree, oldest first.
+async fn history_handler(State(target): State<Arc<ServeTarget>>) -> impl IntoResponse {
+    read_snapshots(target, |store| match store.list() {
+        Ok(list) => json_response(&list),
+        Err(e) => error_response(format!("Reading snapshots failed: {}", e)),
+    })
+    .await
+}
+
+/// One snapshot, tree included.
+async fn snapshot_handler(
+    State(target): State<Arc<ServeTarget>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    read_snapshots(target, move |store| match store.get(&id) {
+        Ok(Some(snapshot)) => json_response(&snapshot),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            #[allow(clippy::disallowed_methods)]
+            Json(serde_json::json!({ "error": format!("no snapshot {}", id) })),
+        ),
+        Err(e) => error_response(format!("Reading snapshot {} failed: {}", id, e)),
+    })
+    .await
+}
+
+#[derive(Serialize)]
+struct Series {
+    metric: String,
+    points: Vec<SeriesPoint>,
+}
+
+/// One metric across all snapshots, e.g. `/api/history/series/loc` or
+/// `/api/history/series/advanced.maintainability_index`.
+async fn series_handler(
+    State(target): State<Arc<ServeTarget>>,
+    Path(metric): Path<String>,
+) -> impl IntoResponse {
+    read_snapshots(target, move |store| match store.series(&metric) {
+        Ok(Some(points)) => json_response(&Series { metric, points }),
+        Ok(None) => (
+            StatusCode::BAD_REQUEST,
+            #[allow(clippy::disallowed_methods)]
+            Json(serde_json::json!({ "error": format!("unknown metric {}", metric) })),
+        ),
+        Err(e) => error_response(format!("Reading snapshots failed: {}", e)),
+    })
+    .await
+}
+
+/// CodeCharta exports of large monorepos easily exceed axum's 2 MB default.
+const IMPORT_BODY_LIMIT: usize = 256 * 1024 * 1024;
+
+#[derive(Deserialize)]
+struct ImportParams {
+    /// Merge into the tree currently shown instead of replacing it.
+    #[serde(default)]
+    merge: bool,
+}
+
+/// Accepts a ryoiki or CodeCharta `cc.json` (optionally gzipped) and makes it
+/// the tree the viewer shows.
+async fn import_handler(
+    State(target): State<Arc<ServeTarget>>,
+    Query(params): Query<ImportParams>,
+    body: Bytes,
+) -> impl IntoResponse {
+    println!("Import requested ({} bytes)", body.len());
+    // Decompressing, parsing, merging and writing the tree block; keep them
+    // off the runtime's worker threads.
+    let imported =
+        tokio::task::spawn_blocking(move || import_tree(&target, params.merge, &body)).await;
+    match imported {
+        Ok(Ok(v)) => (StatusCode::OK, Json(v)),
+        Ok(Err(resp)) => resp,
+        Err(e) => {
+            eprintln!("Import task failed: {}", e);
+            error_response(e.to_string())
+        }
+    }
+}
+
+/// Parses `body`, merges it into the current tree if asked to, writes the
+/// result where the viewer reads it and returns it as JSON.
+fn import_tree(
+    target: &ServeTarget,
+    merge: bool,
+    body: &[u8],
+) -> Result<serde_json::Value, ApiResponse> {
+    let imported = parse_tree(body).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            #[allow(clippy::disallowed_methods)]
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+    })?;
+    let cfg = ScanConfig::from_tools_config(
+        &target.project_root,
+        &ConfigOverrides::for_dir(target.root.as_deref()),
+    )
+    .map_err(|e| error_response(e.to_string()))?;
+    let Some(web_dir) = cfg.outputs.web_dir.or(cfg.outputs.metrics_dir) else {
+        return Err(error_response("no output directory configured".to_string()));
+    };
+    let tree_path = web_dir.join("ryoiki.cc.json");
+    let tree = if merge {
+        match fs::read(&tree_path).ok().and_then(|b| parse_tree(&b).ok()) {
+            Some(current) => merge_trees(vec![current, imported]),
+            None => Some(imported),
+        }
+    } else {
+        Some(imported)
+    };
+    let Some(tree) = tree else {
+        return Err(error_response("nothing to import".to_string()));
+    };
+    let json = serde_json::to_string_pretty(&tree)
+        .map_err(|e| error_response(format!("Invalid JSON generated: {}", e)))?;
+    fs::create_dir_all(&web_dir)
+        .and_then(|_| fs::write(&tree_path, &json))
+        .map_err(|e| error_response(format!("Writing {} failed: {}", tree_path.display(), e)))?;
+    serde_json::to_value(&tree)
+        .map_err(|e| error_response(format!("Invalid JSON generated: {}", e)))
+}
+
+fn error_response(message: String) -> ApiResponse {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        #[allow(clippy::disallowed_methods)]
+        Json(serde_json::json!({ "error": message })),
+    )
+}
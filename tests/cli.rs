@@ -0,0 +1,235 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+fn fixture(name: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("ryoiki-cli-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(root.join("src")).expect("create fixture dir");
+    fs::write(root.join("src/main.rs"), "fn main() {}\n").expect("write fixture");
+    root
+}
+
+#[test]
+fn scan_writes_outputs_and_prints_summary() {
+    let root = fixture("scan");
+    let out = root.join("out");
+    let res = Command::new(env!("CARGO_BIN_EXE_ryoiki"))
+        .arg("scan")
+        .arg(&root)
+        .arg("--out")
+        .arg(&out)
+        .output()
+        .expect("run ryoiki scan");
+    assert!(res.status.success());
+    let v: serde_json::Value = serde_json::from_slice(&res.stdout).expect("stdout is JSON");
+    assert_eq!(v["totals"]["files"].as_u64(), Some(1));
+    assert!(out.join("ryoiki.cc.json").is_file());
+    assert!(out.join("ryoiki.metrics.json").is_file());
+    let index = fs::read(out.join("history/index.json")).expect("snapshot index");
+    let index: serde_json::Value = serde_json::from_slice(&index).expect("index is JSON");
+    assert_eq!(index.as_array().map(Vec::len), Some(1));
+    assert_eq!(index[0]["totals"]["files"].as_u64(), Some(1));
+    let _ = fs::remove_dir_all(&root);
+}
+
+#[test]
+fn scan_depth_functions_adds_function_nodes() {
+    let root = fixture("depth");
+    let out = root.join("out");
+    let scan = |depth: &str| {
+        Command::new(env!("CARGO_BIN_EXE_ryoiki"))
+            .args(["scan", "--depth", depth, "--no-cache"])
+            .arg(&root)
+            .arg("--out")
+            .arg(&out)
+            .output()
+            .expect("run ryoiki scan")
+    };
+    assert_eq!(scan("lines").status.code(), Some(2));
+    assert!(scan("functions").status.success());
+    let tree = fs::read(out.join("ryoiki.cc.json")).expect("tree");
+    let tree: serde_json::Value = serde_json::from_slice(&tree).expect("tree is JSON");
+    let main = &tree["children"][0]["children"][0]["children"][0];
+    assert_eq!(main["kind"], "function");
+    assert_eq!(main["path"], "src/main.rs#main");
+    assert_eq!(main["function"]["end_line"].as_u64(), Some(1));
+    let _ = fs::remove_dir_all(&root);
+}
+
+#[test]
+fn timeline_writes_series_and_snapshots() {
+    let root = fixture("timeline");
+    let git = |args: &[&str]| {
+        let status = Command::new("git")
+            .args(["-c", "user.name=t", "-c", "user.email=t@x"])
+            .args(args)
+            .current_dir(&root)
+            .status()
+            .expect("run git");
+        assert!(status.success(), "git {:?}", args);
+    };
+    git(&["init", "-q"]);
+    git(&["add", "-A"]);
+    git(&["commit", "-qm", "one"]);
+    fs::write(root.join("src/lib.rs"), "fn a() {}\n").expect("write fixture");
+    git(&["add", "-A"]);
+    git(&["commit", "-qm", "two"]);
+
+    let out = root.join("out");
+    let res = Command::new(env!("CARGO_BIN_EXE_ryoiki"))
+        .args([
+            "timeline", "--from", "HEAD~1", "--every", "1", "--trees", "--out",
+        ])
+        .arg(&out)
+        .arg(&root)
+        .output()
+        .expect("run ryoiki timeline");
+    assert!(
+        res.status.success(),
+        "{}",
+        String::from_utf8_lossy(&res.stderr)
+    );
+    let v: serde_json::Value = serde_json::from_slice(&res.stdout).expect("stdout is JSON");
+    assert_eq!(v["timeline"][1]["totals"]["files"].as_u64(), Some(2));
+    let metrics = fs::read(out.join("ryoiki.metrics.json")).expect("metrics written");
+    let metrics: serde_json::Value = serde_json::from_slice(&metrics).expect("metrics is JSON");
+    let files: Vec<u64> = metrics["timeline"]
+        .as_array()
+        .expect("timeline")
+        .iter()
+        .filter_map(|p| p["totals"]["files"].as_u64())
+        .collect();
+    assert_eq!(files, [1, 2]);
+    let index = fs::read(out.join("history/index.json")).expect("snapshot index");
+    let index: serde_json::Value = serde_json::from_slice(&index).expect("index is JSON");
+    assert_eq!(index.as_array().map(Vec::len), Some(2));
+
+    let bad = Command::new(env!("CARGO_BIN_EXE_ryoiki"))
+        .args(["timeline", "--from", "HEAD~1", "--every", "2 weeks"])
+        .arg(&root)
+        .output()
+        .expect("run ryoiki timeline");
+    assert_eq!(bad.status.code(), Some(2));
+    let _ = fs::remove_dir_all(&root);
+}
+
+#[test]
+fn diff_exit_code_reflects_changes() {
+    let root = fixture("diff");
+    let snapshot = root.join("before.cc.json");
+    let res = Command::new(env!("CARGO_BIN_EXE_ryoiki"))
+        .args(["export", "--format", "tree", "--output"])
+        .arg(&snapshot)
+        .arg(root.join("src"))
+        .output()
+        .expect("run ryoiki export");
+    assert!(res.status.success());
+
+    let same = Command::new(env!("CARGO_BIN_EXE_ryoiki"))
+        .arg("diff")
+        .arg(&snapshot)
+        .arg(root.join("src"))
+        .output()
+        .expect("run ryoiki diff");
+    assert_eq!(same.status.code(), Some(0));
+
+    fs::write(root.join("src/lib.rs"), "fn a() {}\n").expect("write fixture");
+    let changed = Command::new(env!("CARGO_BIN_EXE_ryoiki"))
+        .arg("diff")
+        .arg(&snapshot)
+        .arg(root.join("src"))
+        .output()
+        .expect("run ryoiki diff");
+    assert_eq!(changed.status.code(), Some(1));
+    let v: serde_json::Value = serde_json::from_slice(&changed.stdout).expect("stdout is JSON");
+    assert_eq!(v["added"][0].as_str(), Some("lib.rs"));
+
+    let missing = Command::new(env!("CARGO_BIN_EXE_ryoiki"))
+        .arg("diff")
+        .arg(root.join("missing.json"))
+        .arg(root.join("src"))
+        .output()
+        .expect("run ryoiki diff");
+    assert_eq!(missing.status.code(), Some(2));
+    let _ = fs::remove_dir_all(&root);
+}
+
+#[test]
+fn delta_marks_changed_and_added_files() {
+    let root = fixture("delta");
+    let snapshot = root.join("before.cc.json");
+    let res = Command::new(env!("CARGO_BIN_EXE_ryoiki"))
+        .args(["export", "--format", "tree", "--output"])
+        .arg(&snapshot)
+        .arg(root.join("src"))
+        .output()
+        .expect("run ryoiki export");
+    assert!(res.status.success());
+    fs::write(root.join("src/main.rs"), "fn main() {}\nfn b() {}\n").expect("write fixture");
+    fs::write(root.join("src/lib.rs"), "fn a() {}\n").expect("write fixture");
+
+    let res = Command::new(env!("CARGO_BIN_EXE_ryoiki"))
+        .arg("delta")
+        .arg(&snapshot)
+        .arg(root.join("src"))
+        .output()
+        .expect("run ryoiki delta");
+    let _ = fs::remove_dir_all(&root);
+    assert!(res.status.success());
+    let v: serde_json::Value = serde_json::from_slice(&res.stdout).expect("stdout is JSON");
+    assert_eq!(v["status"].as_str(), Some("changed"));
+    assert_eq!(v["delta"]["functions"].as_i64(), Some(2));
+    let children = v["children"].as_array().expect("children");
+    assert_eq!(children[0]["path"].as_str(), Some("lib.rs"));
+    assert_eq!(children[0]["status"].as_str(), Some("added"));
+    assert_eq!(children[1]["status"].as_str(), Some("changed"));
+    assert_eq!(children[1]["before"]["functions"].as_u64(), Some(1));
+    assert_eq!(children[1]["after"]["functions"].as_u64(), Some(2));
+}
+
+#[test]
+fn config_prints_layered_settings_and_rejects_unknown_keys() {
+    let root = fixture("config");
+    fs::write(
+        root.join("tools.config.toml"),
+        "[paths]\naudit_dir = \"src\"\n\n[scan]\nexclude = [\"gen\"]\n",
+    )
+    .expect("write config");
+    let run = |env: Option<&str>| {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_ryoiki"));
+        cmd.arg("config")
+            .current_dir(&root)
+            .env_remove("RYOIKI_AUDIT_DIR")
+            .env_remove("TOKADO_AUDIT_DIR")
+            .env_remove("RYOIKI_METRICS_DIR");
+        if let Some(dir) = env {
+            cmd.env("RYOIKI_AUDIT_DIR", dir);
+        }
+        cmd.output().expect("run ryoiki config")
+    };
+
+    let from_file = run(None);
+    assert!(from_file.status.success());
+    let v: serde_json::Value = serde_json::from_slice(&from_file.stdout).expect("stdout is JSON");
+    assert!(v["root"].as_str().is_some_and(|r| r.ends_with("src")));
+    assert!(
+        v["excludes"]
+            .as_array()
+            .is_some_and(|e| e.contains(&"gen".into()))
+    );
+
+    let from_env = run(Some("elsewhere"));
+    let v: serde_json::Value = serde_json::from_slice(&from_env.stdout).expect("stdout is JSON");
+    assert!(v["root"].as_str().is_some_and(|r| r.ends_with("elsewhere")));
+
+    fs::write(
+        root.join("tools.config.toml"),
+        "[paths]\naudit_dri = \"src\"\n",
+    )
+    .expect("write config");
+    let invalid = run(None);
+    let _ = fs::remove_dir_all(&root);
+    assert_eq!(invalid.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&invalid.stderr).contains("audit_dri"));
+}